@@ -0,0 +1,216 @@
+use crate::{Failure, TestCase, TestSuite};
+use quote::{quote, ToTokens};
+use std::collections::HashMap;
+use syn::{File, FnArg, Item, ItemFn, PatType, Receiver, Signature};
+use weblab_assignment_structure::api::public_api;
+
+/// weblab-rs puts comments starting with this at the top of the spec test,
+/// one for every public item in the solution template, formatted as
+/// `path = signature`.
+const SIGNATURE_PREFIX: &str = "// weblab-signature:";
+
+pub struct ApiItem {
+    pub path: String,
+    pub signature: String,
+}
+
+pub fn requested_api(spec_test: &str) -> Vec<ApiItem> {
+    spec_test
+        .lines()
+        .filter_map(|i| i.trim().strip_prefix(SIGNATURE_PREFIX))
+        .filter_map(|i| i.trim().split_once(" = "))
+        .map(|(path, signature)| ApiItem {
+            path: path.to_string(),
+            signature: signature.to_string(),
+        })
+        .collect()
+}
+
+/// Signatures are compared after parsing them, so differences in whitespace
+/// don't matter.
+fn parse_signature(signature: &str) -> Option<Signature> {
+    syn::parse_str(signature).ok()
+}
+
+/// The parts of a signature that matter to code calling the function: not
+/// the names of the arguments, or whether they're `mut`.
+fn signature_key(signature: &Signature) -> String {
+    let Signature {
+        asyncness,
+        unsafety,
+        abi,
+        ident,
+        generics,
+        inputs,
+        output,
+        ..
+    } = signature;
+
+    let inputs = inputs.iter().map(|i| match i {
+        FnArg::Receiver(Receiver {
+            reference: Some((and, lifetime)),
+            mutability,
+            ..
+        }) => quote! {#and #lifetime #mutability self},
+        FnArg::Receiver(_) => quote! {self},
+        FnArg::Typed(PatType { ty, .. }) => ty.to_token_stream(),
+    });
+    let where_clause = &generics.where_clause;
+
+    quote! {#asyncness #unsafety #abi fn #ident #generics (#(#inputs),*) #output #where_clause}
+        .to_string()
+}
+
+fn same_signature(a: &Signature, b: &Signature) -> bool {
+    signature_key(a) == signature_key(b)
+}
+
+fn pretty_signature(signature: &Signature) -> String {
+    let f: ItemFn = syn::parse_quote!(#signature {});
+    let pretty = prettyplease::unparse(&File {
+        shebang: None,
+        attrs: Vec::new(),
+        items: vec![Item::Fn(f)],
+    });
+
+    pretty
+        .trim()
+        .trim_end_matches('}')
+        .trim_end()
+        .trim_end_matches('{')
+        .trim_end()
+        .to_string()
+}
+
+fn kind_name(signature: &str) -> &str {
+    signature.split_whitespace().next().unwrap_or("item")
+}
+
+fn check_item(expected: &ApiItem, found: Option<&String>) -> Option<String> {
+    let expected_sig = parse_signature(&expected.signature);
+    let (kind, pretty_expected) = match &expected_sig {
+        Some(s) if s.receiver().is_some() => ("method", pretty_signature(s)),
+        Some(s) => ("function", pretty_signature(s)),
+        None => (kind_name(&expected.signature), expected.signature.clone()),
+    };
+
+    let found = match found {
+        Some(i) => i,
+        None => return Some(format!("{kind} `{}` is missing", expected.path)),
+    };
+
+    match (&expected_sig, parse_signature(found)) {
+        (Some(e), Some(f)) if same_signature(e, &f) => None,
+        (Some(_), Some(f)) => Some(format!(
+            "expected `{pretty_expected}`, but found `{}`",
+            pretty_signature(&f)
+        )),
+        (None, None) if *found == expected.signature => None,
+        _ => Some(format!("expected `{pretty_expected}`, but found `{found}`")),
+    }
+}
+
+pub fn run_checks(solution: &str, api: &[ApiItem]) -> TestSuite {
+    let mut found = HashMap::new();
+    let parse_error = match syn::parse_file(solution) {
+        Ok(f) => {
+            found.extend(
+                public_api(&f.items)
+                    .into_iter()
+                    .map(|i| (i.path, i.signature)),
+            );
+            None
+        }
+        Err(e) => Some(format!("could not parse the solution: {e}")),
+    };
+
+    TestSuite {
+        testcases: api
+            .iter()
+            .map(|i| TestCase {
+                name: format!("signature: {}", i.path),
+                placeholder: "".to_string(),
+                failure: parse_error
+                    .clone()
+                    .or_else(|| check_item(i, found.get(&i.path)))
+                    .map(|message| Failure { message }),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(template: &str, solution: &str) -> Vec<Option<String>> {
+        let api = public_api(&syn::parse_file(template).unwrap().items)
+            .into_iter()
+            .map(|i| ApiItem {
+                path: i.path,
+                signature: i.signature,
+            })
+            .collect::<Vec<_>>();
+
+        run_checks(solution, &api)
+            .testcases
+            .into_iter()
+            .map(|i| i.failure.map(|f| f.message))
+            .collect()
+    }
+
+    #[test]
+    fn argument_names_and_mut() {
+        let template = "pub fn foo(n: u32, v: &mut Vec<u32>) -> u32 { todo!() }";
+        let solution = "pub fn foo(mut x: u32, (v): &mut Vec<u32>) -> u32 { x }";
+        assert_eq!(check(template, solution), [None]);
+    }
+
+    #[test]
+    fn different_types() {
+        let template = "pub fn foo(n: u32) -> u32 { todo!() }";
+        let failures = check(template, "pub fn foo(n: u64) -> u32 { 0 }");
+        assert_eq!(
+            failures[0].as_deref(),
+            Some("expected `fn foo(n: u32) -> u32`, but found `fn foo(n: u64) -> u32`")
+        );
+        assert!(check(template, "pub fn foo(n: u32) { }")[0].is_some());
+        assert!(check(template, "pub fn foo<T>(n: u32) -> u32 { 0 }")[0].is_some());
+    }
+
+    #[test]
+    fn receivers() {
+        let template =
+            "pub struct S; impl S { pub fn get(&self) -> u32 { todo!() } pub fn take(self) {} }";
+        assert_eq!(
+            check(
+                template,
+                "pub struct S; impl S { pub fn get(&self) -> u32 { 0 } pub fn take(mut self) {} }"
+            ),
+            [None, None, None]
+        );
+        assert!(check(
+            template,
+            "pub struct S; impl S { pub fn get(&mut self) -> u32 { 0 } pub fn take(self) {} }"
+        )[1]
+        .is_some());
+    }
+
+    #[test]
+    fn where_clauses() {
+        let template = "pub fn foo<T>(t: T) where T: Clone {}";
+        assert_eq!(
+            check(template, "pub fn foo<T>(x: T) where T: Clone {}"),
+            [None]
+        );
+        assert!(check(template, "pub fn foo<T>(x: T) where T: Copy {}")[0].is_some());
+    }
+
+    #[test]
+    fn missing_items() {
+        let template = "pub struct Stack; pub fn new() -> Stack { Stack }";
+        let failures = check(template, "pub struct Stack;");
+        assert_eq!(failures[0], None);
+        assert_eq!(failures[1].as_deref(), Some("function `new` is missing"));
+    }
+}
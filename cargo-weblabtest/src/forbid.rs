@@ -0,0 +1,397 @@
+use crate::{Failure, TestCase, TestSuite};
+use proc_macro2::{Spacing, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{UseGlob, UseGroup, UseName, UsePath, UseRename, UseTree};
+
+/// weblab-rs puts comments starting with this at the top of the spec test,
+/// one for every construct the solution may not use.
+const FORBID_PREFIX: &str = "// weblab-forbid:";
+
+#[derive(Debug, PartialEq)]
+pub enum Forbidden {
+    Keyword(String),
+    Path(Vec<String>),
+    Macro(String),
+}
+
+impl Forbidden {
+    fn name(&self) -> String {
+        match self {
+            Forbidden::Keyword(k) => k.clone(),
+            Forbidden::Path(p) => p.join("::"),
+            Forbidden::Macro(m) => format!("{m}!"),
+        }
+    }
+}
+
+pub fn requested_forbidden(spec_test: &str) -> Vec<Forbidden> {
+    spec_test
+        .lines()
+        .filter_map(|i| i.trim().strip_prefix(FORBID_PREFIX))
+        .filter_map(|i| i.trim().split_once(' '))
+        .filter_map(|(kind, value)| match kind {
+            "keyword" => Some(Forbidden::Keyword(value.to_string())),
+            "path" => Some(Forbidden::Path(
+                value.split("::").map(|i| i.trim().to_string()).collect(),
+            )),
+            "macro" => Some(Forbidden::Macro(
+                value.rsplit("::").next().unwrap_or(value).to_string(),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A path, macro invocation or keyword found in the solution.
+#[derive(Debug, PartialEq)]
+enum Occurrence {
+    Keyword(String),
+    Path(Vec<String>),
+    Macro(String),
+    /// a field or method, like `sort` in `v.sort()`
+    Member(String),
+}
+
+/// A name brought into scope by a `use` in the solution
+#[derive(Debug, PartialEq)]
+enum Import {
+    /// `use a::b::c;` (name `c`) or `use a::b::c as name;`
+    Name { name: String, path: Vec<String> },
+    /// `use a::b::*;`
+    Glob(Vec<String>),
+}
+
+/// Everything named in the solution, with the lines it's on
+#[derive(Default)]
+struct Occurrences {
+    found: Vec<(Occurrence, usize)>,
+    imports: Vec<Import>,
+}
+
+impl Occurrences {
+    /// Finds all keywords, paths and macro invocations in a token stream,
+    /// including the ones inside macro invocations. Names after a `.` are
+    /// fields and methods, not paths.
+    fn find(&mut self, stream: TokenStream) {
+        let tokens = stream.into_iter().collect::<Vec<_>>();
+        let mut path: Vec<String> = Vec::new();
+        let mut after_colons = false;
+        let mut after_dot = false;
+        let mut member = false;
+        let mut line = 0;
+
+        let mut idx = 0;
+        while idx < tokens.len() {
+            match &tokens[idx] {
+                TokenTree::Ident(i) if i == "use" && !after_colons => {
+                    self.flush(&mut path, member, line);
+                    self.found
+                        .push((Occurrence::Keyword(i.to_string()), i.span().start().line));
+                    // the use tree, up to the `;`
+                    let end = tokens[idx..]
+                        .iter()
+                        .position(|i| matches!(i, TokenTree::Punct(p) if p.as_char() == ';'))
+                        .map_or(tokens.len(), |i| idx + i);
+                    let tree = tokens[idx + 1..end].iter().cloned().collect();
+                    if let Ok(tree) = syn::parse2::<UseTree>(tree) {
+                        self.add_use(&tree, Vec::new());
+                        idx = end;
+                    }
+                    after_dot = false;
+                }
+                TokenTree::Ident(i) => {
+                    let name = i.to_string();
+                    self.found
+                        .push((Occurrence::Keyword(name.clone()), i.span().start().line));
+
+                    if !after_colons {
+                        self.flush(&mut path, member, line);
+                        member = after_dot;
+                    }
+                    if path.is_empty() {
+                        line = i.span().start().line;
+                    }
+                    path.push(name);
+                    after_colons = false;
+                    after_dot = false;
+                }
+                TokenTree::Punct(p)
+                    if p.as_char() == ':'
+                        && p.spacing() == Spacing::Joint
+                        && idx + 1 < tokens.len() =>
+                {
+                    // skip the second colon of `::`
+                    idx += 1;
+                    after_colons = true;
+                }
+                TokenTree::Punct(p) if p.as_char() == '!' && p.spacing() == Spacing::Alone => {
+                    if let (Some(name), Some(TokenTree::Group(_)), false) =
+                        (path.last(), tokens.get(idx + 1), member)
+                    {
+                        self.found.push((Occurrence::Macro(name.clone()), line));
+                    }
+                    self.flush(&mut path, member, line);
+                    after_colons = false;
+                    after_dot = false;
+                }
+                TokenTree::Group(g) => {
+                    self.flush(&mut path, member, line);
+                    self.find(g.stream());
+                    after_colons = false;
+                    after_dot = false;
+                }
+                TokenTree::Punct(p) => {
+                    self.flush(&mut path, member, line);
+                    // a `.` that isn't part of `..`
+                    let joint_dot = |i: usize| {
+                        matches!(tokens.get(i), Some(TokenTree::Punct(p))
+                            if p.as_char() == '.' && p.spacing() == Spacing::Joint)
+                    };
+                    after_dot = p.as_char() == '.'
+                        && p.spacing() == Spacing::Alone
+                        && !idx.checked_sub(1).is_some_and(joint_dot);
+                    after_colons = false;
+                }
+                TokenTree::Literal(_) => {
+                    self.flush(&mut path, member, line);
+                    after_colons = false;
+                    after_dot = false;
+                }
+            }
+
+            idx += 1;
+        }
+
+        self.flush(&mut path, member, line);
+    }
+
+    fn flush(&mut self, path: &mut Vec<String>, member: bool, line: usize) {
+        let mut path = std::mem::take(path);
+        if path.is_empty() {
+            return;
+        }
+
+        if member {
+            self.found.push((Occurrence::Member(path.remove(0)), line));
+        } else {
+            self.found.push((Occurrence::Path(path), line));
+        }
+    }
+
+    /// Records the imports of a use tree, and the paths it imports as occurrences
+    fn add_use(&mut self, tree: &UseTree, mut prefix: Vec<String>) {
+        match tree {
+            UseTree::Path(UsePath { ident, tree, .. }) => {
+                prefix.push(ident.to_string());
+                self.add_use(tree, prefix);
+            }
+            UseTree::Name(UseName { ident }) | UseTree::Rename(UseRename { ident, .. }) => {
+                // `use a::b::{self}` imports `b`
+                if ident != "self" {
+                    prefix.push(ident.to_string());
+                }
+                let name = match tree {
+                    UseTree::Rename(UseRename { rename, .. }) => rename.to_string(),
+                    _ => prefix.last().cloned().unwrap_or_default(),
+                };
+
+                let line = ident.span().start().line;
+                self.found.push((Occurrence::Path(prefix.clone()), line));
+                self.imports.push(Import::Name { name, path: prefix });
+            }
+            UseTree::Glob(UseGlob { star_token }) => {
+                let line = star_token.spans[0].start().line;
+                self.found.push((Occurrence::Path(prefix.clone()), line));
+                self.imports.push(Import::Glob(prefix));
+            }
+            UseTree::Group(UseGroup { items, .. }) => {
+                for i in items {
+                    self.add_use(i, prefix.clone());
+                }
+            }
+        }
+    }
+
+    /// The paths `path` may refer to, given the imports in the solution
+    fn resolve<'a>(&'a self, path: &'a [String]) -> impl Iterator<Item = Vec<String>> + 'a {
+        let imported = self.imports.iter().filter_map(move |i| match i {
+            Import::Name { name, path: full } if *name == path[0] => {
+                Some(full.iter().chain(&path[1..]).cloned().collect())
+            }
+            Import::Glob(prefix) => Some(prefix.iter().chain(path).cloned().collect()),
+            Import::Name { .. } => None,
+        });
+        std::iter::once(path.to_vec()).chain(imported)
+    }
+
+    fn matches(&self, occurrence: &Occurrence, forbidden: &Forbidden) -> bool {
+        match (occurrence, forbidden) {
+            (Occurrence::Keyword(a), Forbidden::Keyword(b)) => a == b,
+            (Occurrence::Macro(a), Forbidden::Macro(b)) => a == b,
+            // `HashMap::new()` refers to `std::collections::HashMap` when that's
+            // imported, so a path matches when the forbidden path is part of
+            // what it refers to.
+            (Occurrence::Path(a), Forbidden::Path(b)) => self
+                .resolve(a)
+                .any(|full| full.windows(b.len()).any(|w| w == b.as_slice())),
+            // a single name like `sort` also forbids the method `v.sort()`
+            (Occurrence::Member(a), Forbidden::Path(b)) => b.len() == 1 && *a == b[0],
+            _ => false,
+        }
+    }
+
+    /// The lines on which `forbidden` is used
+    fn lines_using(&self, forbidden: &Forbidden) -> Vec<usize> {
+        let mut lines = self
+            .found
+            .iter()
+            .filter(|(o, _)| self.matches(o, forbidden))
+            .map(|(_, line)| *line)
+            .collect::<Vec<_>>();
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    }
+}
+
+pub fn run_checks(solution: &str, forbidden: &[Forbidden]) -> TestSuite {
+    let file = match syn::parse_file(solution) {
+        Ok(i) => i,
+        Err(e) => {
+            return TestSuite {
+                testcases: forbidden
+                    .iter()
+                    .map(|i| TestCase {
+                        name: format!("forbidden: {}", i.name()),
+                        placeholder: "".to_string(),
+                        failure: Some(Failure {
+                            message: format!("could not parse the solution: {e}"),
+                        }),
+                    })
+                    .collect(),
+            }
+        }
+    };
+
+    let mut occurrences = Occurrences::default();
+    occurrences.find(file.into_token_stream());
+    let lines = solution.lines().collect::<Vec<_>>();
+
+    let testcases = forbidden
+        .iter()
+        .map(|f| {
+            let offending_lines = occurrences.lines_using(f);
+
+            let violations = offending_lines
+                .iter()
+                .map(|line| {
+                    let text = lines.get(line.wrapping_sub(1)).copied().unwrap_or("");
+                    format!("line {line}: {}", text.trim())
+                })
+                .collect::<Vec<_>>();
+
+            TestCase {
+                name: format!("forbidden: {}", f.name()),
+                placeholder: "".to_string(),
+                failure: (!violations.is_empty()).then(|| Failure {
+                    message: format!(
+                        "`{}` may not be used in this assignment, but it is used here:\n{}",
+                        f.name(),
+                        violations.join("\n")
+                    ),
+                }),
+            }
+        })
+        .collect();
+
+    TestSuite { testcases }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines_using(solution: &str, forbidden: &str) -> Vec<usize> {
+        let forbidden = requested_forbidden(&format!("{FORBID_PREFIX} {forbidden}"));
+        let mut occurrences = Occurrences::default();
+        occurrences.find(solution.parse().unwrap());
+        occurrences.lines_using(&forbidden[0])
+    }
+
+    #[test]
+    fn full_path() {
+        let solution = "fn f(a: &mut u8, b: &mut u8) {\n    std::mem::swap(a, b);\n}";
+        assert_eq!(lines_using(solution, "path std::mem::swap"), [2]);
+    }
+
+    #[test]
+    fn methods_and_locals() {
+        let solution = "fn f(v: &mut Vec<u8>) {\n    v.swap(0, 1);\n    let swap = v.len;\n    v.sort();\n    swap\n}";
+        assert!(lines_using(solution, "path std::mem::swap").is_empty());
+        assert!(lines_using(solution, "path Vec::sort").is_empty());
+        assert_eq!(lines_using(solution, "path sort"), [4]);
+    }
+
+    #[test]
+    fn ranges_are_not_methods() {
+        let solution = "fn f() {\n    let _ = 0..HashMap::new().len();\n}";
+        assert_eq!(lines_using(solution, "path HashMap::new"), [2]);
+    }
+
+    #[test]
+    fn imports() {
+        let solution = "use std::mem;\nfn f(a: &mut u8, b: &mut u8) {\n    mem::swap(a, b);\n}";
+        assert_eq!(lines_using(solution, "path std::mem::swap"), [3]);
+
+        let solution = "use std::mem::swap;\nfn f(a: &mut u8, b: &mut u8) {\n    swap(a, b);\n}";
+        assert_eq!(lines_using(solution, "path std::mem::swap"), [1, 3]);
+
+        let solution = "use std::mem::*;\nfn f(a: &mut u8, b: &mut u8) {\n    swap(a, b);\n}";
+        assert_eq!(lines_using(solution, "path std::mem::swap"), [3]);
+    }
+
+    #[test]
+    fn aliases() {
+        let solution =
+            "use std::collections as c;\nfn f() {\n    let _ = c::HashMap::<u8, u8>::new();\n}";
+        assert_eq!(lines_using(solution, "path std::collections::HashMap"), [3]);
+
+        let solution =
+            "use std::collections::HashMap as M;\nfn f() {\n    let _ = M::<u8, u8>::new();\n}";
+        assert_eq!(
+            lines_using(solution, "path std::collections::HashMap"),
+            [1, 3]
+        );
+    }
+
+    #[test]
+    fn nested_use_groups() {
+        let solution = "use std::{\n    collections::{BTreeMap, HashMap},\n    mem,\n};";
+        assert_eq!(lines_using(solution, "path std::collections::HashMap"), [2]);
+        assert!(lines_using(solution, "path std::collections::HashSet").is_empty());
+
+        let solution = "use std::collections::{self};\nfn f() {\n    collections::HashMap::<u8, u8>::new();\n}";
+        assert_eq!(lines_using(solution, "path std::collections::HashMap"), [3]);
+    }
+
+    #[test]
+    fn macros() {
+        let solution = "fn f(x: u8) {\n    println!(\"{}\", x);\n    std::println!(\"{}\", x);\n    x.println();\n}";
+        assert_eq!(lines_using(solution, "macro println"), [2, 3]);
+
+        let solution = "fn f(a: &mut u8, b: &mut u8) {\n    assert!(true);\n    debug_assert!({ std::mem::swap(a, b); true });\n}";
+        assert_eq!(lines_using(solution, "path std::mem::swap"), [3]);
+    }
+
+    #[test]
+    fn keywords() {
+        let solution = "fn f() {\n    unsafe {}\n}\nunsafe fn g() {}";
+        assert_eq!(lines_using(solution, "keyword unsafe"), [2, 4]);
+    }
+
+    #[test]
+    fn sorted_lines() {
+        let solution = "fn f(a: &mut u8, b: &mut u8) {\n    std::mem::swap(a, b);\n    std::mem::swap(b, a);\n    std::mem::swap(a, b);\n}\nuse std::mem::swap;";
+        assert_eq!(lines_using(solution, "path std::mem::swap"), [2, 3, 4, 6]);
+    }
+}
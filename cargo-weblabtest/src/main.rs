@@ -9,6 +9,10 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::process::Command;
 
+mod api;
+mod forbid;
+mod style;
+
 #[xml_element("testsuites")]
 struct TestSuites {
     #[sxs_type_multi_element]
@@ -54,9 +58,17 @@ enum TestEventType {
     #[serde(rename = "started")]
     Started { name: String },
     #[serde(rename = "failed")]
-    Failed { name: String },
+    Failed {
+        name: String,
+        stdout: Option<String>,
+    },
     #[serde(rename = "ok")]
-    Ok { name: String },
+    Ok {
+        name: String,
+        stdout: Option<String>,
+    },
+    #[serde(rename = "ignored")]
+    Ignored { name: String },
 }
 
 #[derive(Deserialize)]
@@ -66,6 +78,8 @@ enum SuiteEventType {
     Ok {
         passed: usize,
         failed: usize,
+        // not reported by newer versions of libtest
+        #[serde(default)]
         allowed_fail: usize,
         ignored: usize,
         measured: usize,
@@ -75,6 +89,8 @@ enum SuiteEventType {
     Failed {
         passed: usize,
         failed: usize,
+        // not reported by newer versions of libtest
+        #[serde(default)]
         allowed_fail: usize,
         ignored: usize,
         measured: usize,
@@ -106,11 +122,52 @@ enum TestReportMessage {
     },
 }
 
-fn parse_test_output(stream: &[u8]) -> (Vec<TestReportMessage>, Vec<u8>) {
+/// Printed by tests marked with `#[weblab(performance)]`, followed by a json
+/// encoded [`PerformanceReport`]
+const PERFORMANCE_PREFIX: &[u8] = b"weblab-performance ";
+
+#[derive(Deserialize)]
+struct PerformanceReport {
+    name: String,
+    reference_ns: u64,
+    solution_ns: u64,
+    max_slowdown: f64,
+}
+
+impl PerformanceReport {
+    fn ratio(&self) -> f64 {
+        self.solution_ns as f64 / self.reference_ns.max(1) as f64
+    }
+
+    fn too_slow(&self) -> bool {
+        self.ratio() > self.max_slowdown
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{}: the solution took {:.2}x as long as the reference solution (at most {}x is allowed)",
+            self.name,
+            self.ratio(),
+            self.max_slowdown
+        )
+    }
+}
+
+fn parse_performance_report(line: &[u8]) -> Option<PerformanceReport> {
+    serde_json::from_slice(line.strip_prefix(PERFORMANCE_PREFIX)?).ok()
+}
+
+fn parse_test_output(stream: &[u8]) -> (Vec<TestReportMessage>, Vec<PerformanceReport>, Vec<u8>) {
     let mut res = Vec::new();
+    let mut performance = Vec::new();
     let mut res_stream = Vec::new();
 
     for i in stream.split(|i| i == &b'\n') {
+        if let Some(report) = parse_performance_report(i) {
+            performance.push(report);
+            continue;
+        }
+
         match serde_json::from_slice(i) {
             Ok(i) => res.push(i),
             Err(_) => {
@@ -121,7 +178,95 @@ fn parse_test_output(stream: &[u8]) -> (Vec<TestReportMessage>, Vec<u8>) {
         }
     }
 
-    (res, res_stream)
+    (res, performance, res_stream)
+}
+
+/// What a single test printed, as captured by libtest
+struct TestOutput {
+    name: String,
+    passed: bool,
+    stdout: String,
+}
+
+/// Collects the captured output of every test. Performance reports printed by tests
+/// are taken out of the output and added to `performance`.
+fn collect_test_output(
+    messages: &[TestReportMessage],
+    performance: &mut Vec<PerformanceReport>,
+) -> Vec<TestOutput> {
+    let mut res = Vec::new();
+
+    for msg in messages {
+        let (name, passed, stdout) = match msg {
+            TestReportMessage::Test {
+                event: TestEventType::Ok { name, stdout },
+                ..
+            } => (name, true, stdout),
+            TestReportMessage::Test {
+                event: TestEventType::Failed { name, stdout },
+                ..
+            } => (name, false, stdout),
+            _ => continue,
+        };
+
+        let mut output = String::new();
+        for line in stdout.as_deref().unwrap_or_default().lines() {
+            match parse_performance_report(line.as_bytes()) {
+                Some(report) => performance.push(report),
+                None => {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            }
+        }
+
+        res.push(TestOutput {
+            name: name.clone(),
+            passed,
+            stdout: output,
+        });
+    }
+
+    res.sort_by(|a, b| a.name.cmp(&b.name));
+    res
+}
+
+/// Formats stdout.txt with a section for every test that printed something, so
+/// students can see which test printed what.
+fn format_stdout(
+    tests: &[TestOutput],
+    performance: &[PerformanceReport],
+    other_output: &[u8],
+) -> String {
+    let mut res = String::new();
+
+    for i in tests.iter().filter(|i| !i.stdout.trim().is_empty()) {
+        let status = if i.passed { "passed" } else { "failed" };
+        res.push_str(&format!(
+            "==== {} ({status}) ====\n{}\n\n",
+            i.name,
+            i.stdout.trim_end()
+        ));
+    }
+
+    if !performance.is_empty() {
+        res.push_str("==== performance ====\n");
+        for i in performance {
+            res.push_str(&i.summary());
+            res.push('\n');
+        }
+        res.push('\n');
+    }
+
+    let other_output = String::from_utf8_lossy(other_output);
+    if !other_output.trim().is_empty() {
+        res.push_str(&format!(
+            "==== output outside of tests ====\n{}\n",
+            other_output.trim_end()
+        ));
+    }
+
+    res
 }
 
 enum TestStatus {
@@ -131,7 +276,7 @@ enum TestStatus {
     Unknown,
 }
 
-fn convert_to_junit(inp: Vec<TestReportMessage>) -> TestSuites {
+fn convert_to_junit(inp: Vec<TestReportMessage>, performance: &[PerformanceReport]) -> TestSuites {
     let mut tests = HashMap::new();
 
     for msg in inp {
@@ -140,12 +285,15 @@ fn convert_to_junit(inp: Vec<TestReportMessage>) -> TestSuites {
                 TestEventType::Started { name } => {
                     tests.insert(name, TestStatus::Unknown);
                 }
-                TestEventType::Ok { name } => {
+                TestEventType::Ok { name, .. } => {
                     tests.insert(name, TestStatus::Passed);
                 }
-                TestEventType::Failed { name } => {
+                TestEventType::Failed { name, .. } => {
                     tests.insert(name, TestStatus::Failed);
                 }
+                TestEventType::Ignored { name } => {
+                    tests.insert(name, TestStatus::Skipped);
+                }
             },
             TestReportMessage::Suite { .. } => {}
         }
@@ -154,11 +302,15 @@ fn convert_to_junit(inp: Vec<TestReportMessage>) -> TestSuites {
     let mut testcases = Vec::new();
 
     for (name, t) in tests {
+        let too_slow = performance.iter().find(|i| i.name == name && i.too_slow());
+
         testcases.push(TestCase {
             name,
             placeholder: "".to_string(),
             failure: match t {
-                TestStatus::Passed => None,
+                TestStatus::Passed => too_slow.map(|i| Failure {
+                    message: i.summary(),
+                }),
                 TestStatus::Failed => Some(Failure {
                     message: "failed".to_string(),
                 }),
@@ -174,6 +326,21 @@ fn convert_to_junit(inp: Vec<TestReportMessage>) -> TestSuites {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let spec_test = std::fs::read_to_string("src/test.rs").unwrap_or_default();
+    let checks = style::requested_checks(&spec_test);
+    let forbidden = forbid::requested_forbidden(&spec_test);
+    let api = api::requested_api(&spec_test);
+    let solution = std::fs::read_to_string("src/solution.rs").unwrap_or_default();
+
+    // done before compiling, a missing or changed function otherwise
+    // only shows up as a compile error in the spec test
+    let api_results = if api.is_empty() {
+        None
+    } else {
+        println!("checking the signatures in the solution...");
+        Some(api::run_checks(&solution, &api))
+    };
+
     println!("running tests...");
     let cmd = Command::new("cargo")
         .arg("test")
@@ -184,11 +351,21 @@ fn main() -> Result<(), Box<dyn Error>> {
         .arg("--format=json")
         .arg("--test-threads")
         .arg("3")
-        .arg("--nocapture")
+        // instead of --nocapture, so the output of every test ends up in its json message
+        .arg("--show-output")
         .arg("-Z")
         .arg("unstable-options")
         .output()?;
 
+    println!("running style checks...");
+    let style_results = style::run_checks(&checks);
+    let forbid_results = if forbidden.is_empty() {
+        None
+    } else {
+        println!("checking for forbidden constructs...");
+        Some(forbid::run_checks(&solution, &forbidden))
+    };
+
     println!("writing stdout and stderr");
 
     let path = PathBuf::from(
@@ -206,12 +383,23 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     println!("parsing test data");
 
-    let (messages, filtered_stdout) = parse_test_output(&stdout);
+    let (messages, mut performance, filtered_stdout) = parse_test_output(&stdout);
+    let test_output = collect_test_output(&messages, &mut performance);
 
-    File::create("stdout.txt")?.write_all(&filtered_stdout)?;
+    File::create("stdout.txt")?
+        .write_all(format_stdout(&test_output, &performance, &filtered_stdout).as_bytes())?;
     File::create("stderr.txt")?.write_all(&stderr)?;
 
-    let junit = convert_to_junit(messages);
+    let mut junit = convert_to_junit(messages, &performance);
+    if !style_results.testcases.is_empty() {
+        junit.testsuites.push(style_results);
+    }
+    if let Some(forbid_results) = forbid_results {
+        junit.testsuites.push(forbid_results);
+    }
+    if let Some(api_results) = api_results {
+        junit.testsuites.push(api_results);
+    }
     let xml = XMLElement::from(junit);
 
     println!("writing xml");
@@ -226,3 +414,176 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(reference_ns: u64, solution_ns: u64, max_slowdown: f64) -> PerformanceReport {
+        PerformanceReport {
+            name: "test::fast".to_string(),
+            reference_ns,
+            solution_ns,
+            max_slowdown,
+        }
+    }
+
+    #[test]
+    fn performance_report() {
+        let line = br#"weblab-performance {"name":"test::fast","reference_ns":100,"solution_ns":250,"max_slowdown":2.0}"#;
+        let report = parse_performance_report(line).unwrap();
+        assert_eq!(report.name, "test::fast");
+        assert_eq!(report.reference_ns, 100);
+        assert_eq!(report.solution_ns, 250);
+        assert_eq!(report.max_slowdown, 2.0);
+        assert!(report.too_slow());
+    }
+
+    #[test]
+    fn garbled_performance_report() {
+        assert!(parse_performance_report(b"").is_none());
+        assert!(parse_performance_report(b"weblab-performance").is_none());
+        assert!(parse_performance_report(b"weblab-performance {\"name\":\"test::fast\"").is_none());
+        assert!(
+            parse_performance_report(b"weblab-performance {\"name\":\"test::fast\"}").is_none()
+        );
+        assert!(parse_performance_report(
+            br#"performance {"name":"test::fast","reference_ns":100,"solution_ns":250,"max_slowdown":2.0}"#
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn too_slow() {
+        // exactly as slow as allowed is still fast enough
+        assert!(!report(100, 200, 2.0).too_slow());
+        assert!(!report(100, 150, 1.5).too_slow());
+        assert!(report(100, 201, 2.0).too_slow());
+        // a reference that took no time at all doesn't divide by zero
+        assert!(!report(0, 1, 1.0).too_slow());
+    }
+
+    #[test]
+    fn too_slow_fails_passing_test() {
+        let messages = [
+            r#"{"type":"test","event":"ok","name":"test::fast"}"#,
+            r#"{"type":"test","event":"ok","name":"test::slow"}"#,
+        ];
+        let messages = messages
+            .iter()
+            .map(|i| serde_json::from_str(i).unwrap())
+            .collect();
+        let mut slow = report(100, 300, 2.0);
+        slow.name = "test::slow".to_string();
+
+        let junit = convert_to_junit(messages, &[report(100, 200, 2.0), slow]);
+        let mut testcases = junit.testsuites[0]
+            .testcases
+            .iter()
+            .map(|i| {
+                (
+                    i.name.as_str(),
+                    i.failure.as_ref().map(|i| i.message.as_str()),
+                )
+            })
+            .collect::<Vec<_>>();
+        testcases.sort();
+        assert_eq!(
+            testcases,
+            [
+                ("test::fast", None),
+                (
+                    "test::slow",
+                    Some(
+                        "test::slow: the solution took 3.00x as long as the reference solution \
+                        (at most 2x is allowed)"
+                    )
+                ),
+            ]
+        );
+    }
+
+    fn messages(lines: &[&str]) -> Vec<TestReportMessage> {
+        lines
+            .iter()
+            .map(|i| serde_json::from_str(i).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn output_per_test() {
+        // tests run in parallel, so they finish in any order
+        let messages = messages(&[
+            r#"{"type":"suite","event":"started","test_count":3}"#,
+            r#"{"type":"test","event":"started","name":"test::b"}"#,
+            r#"{"type":"test","event":"started","name":"test::a"}"#,
+            r#"{"type":"test","event":"started","name":"test::c"}"#,
+            r#"{"type":"test","name":"test::c","event":"ok","stdout":"c1\nc2\n"}"#,
+            r#"{"type":"test","name":"test::a","event":"ok","stdout":"a1\n"}"#,
+            r#"{"type":"test","name":"test::b","event":"ok"}"#,
+        ]);
+        let mut performance = Vec::new();
+        let tests = collect_test_output(&messages, &mut performance);
+
+        let output = tests
+            .iter()
+            .map(|i| (i.name.as_str(), i.passed, i.stdout.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            output,
+            [
+                ("test::a", true, "a1\n"),
+                ("test::b", true, ""),
+                ("test::c", true, "c1\nc2\n"),
+            ]
+        );
+        assert_eq!(
+            format_stdout(&tests, &performance, b""),
+            "==== test::a (passed) ====\na1\n\n==== test::c (passed) ====\nc1\nc2\n\n"
+        );
+    }
+
+    #[test]
+    fn panicking_test() {
+        let messages = messages(&[
+            r#"{"type":"test","name":"test::a","event":"failed","stdout":"before\nthread 'test::a' panicked at src/test.rs:4:5:\nassertion failed: false\n"}"#,
+        ]);
+        let tests = collect_test_output(&messages, &mut Vec::new());
+        assert!(!tests[0].passed);
+        assert_eq!(
+            format_stdout(&tests, &[], b"compiling\n"),
+            "==== test::a (failed) ====\nbefore\nthread 'test::a' panicked at src/test.rs:4:5:\n\
+            assertion failed: false\n\n\
+            ==== output outside of tests ====\ncompiling\n"
+        );
+    }
+
+    #[test]
+    fn performance_in_output() {
+        let messages = messages(&[
+            r#"{"type":"test","name":"test::fast","event":"ok","stdout":"weblab-performance {\"name\":\"test::fast\",\"reference_ns\":100,\"solution_ns\":150,\"max_slowdown\":2.0}\n"}"#,
+        ]);
+        let mut performance = Vec::new();
+        let tests = collect_test_output(&messages, &mut performance);
+        assert_eq!(tests[0].stdout, "");
+        assert_eq!(performance.len(), 1);
+        assert_eq!(
+            format_stdout(&tests, &performance, b""),
+            "==== performance ====\n\
+            test::fast: the solution took 1.50x as long as the reference solution \
+            (at most 2x is allowed)\n\n"
+        );
+    }
+
+    #[test]
+    fn no_output() {
+        let messages = messages(&[
+            r#"{"type":"test","name":"test::a","event":"ok","stdout":""}"#,
+            r#"{"type":"test","name":"test::b","event":"ok","stdout":"\n  \n"}"#,
+            r#"{"type":"test","event":"ignored","name":"test::c"}"#,
+        ]);
+        let tests = collect_test_output(&messages, &mut Vec::new());
+        assert_eq!(tests.len(), 2);
+        assert_eq!(format_stdout(&tests, &[], b"\n"), "");
+    }
+}
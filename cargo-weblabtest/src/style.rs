@@ -0,0 +1,286 @@
+use crate::{Failure, TestCase, TestSuite};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::process::Command;
+
+/// weblab-rs puts comments starting with this at the top of the spec test
+/// to ask for extra checks on the solution.
+const CHECK_PREFIX: &str = "// weblab-check:";
+
+const SOLUTION_FILE: &str = "src/solution.rs";
+
+/// The clippy lint groups that are graded, each one becomes its own test case
+const CLIPPY_GROUPS: &[&str] = &["correctness", "suspicious", "style", "complexity", "perf"];
+
+#[derive(Debug, PartialEq)]
+pub enum StyleCheck {
+    Clippy,
+    Rustfmt,
+}
+
+pub fn requested_checks(spec_test: &str) -> Vec<StyleCheck> {
+    spec_test
+        .lines()
+        .filter_map(|i| i.trim().strip_prefix(CHECK_PREFIX))
+        .filter_map(|i| match i.trim() {
+            "clippy" => Some(StyleCheck::Clippy),
+            "rustfmt" => Some(StyleCheck::Rustfmt),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "reason")]
+enum CargoMessage {
+    #[serde(rename = "compiler-message")]
+    CompilerMessage { message: Diagnostic },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    rendered: Option<String>,
+    level: String,
+    code: Option<DiagnosticCode>,
+    spans: Vec<DiagnosticSpan>,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticSpan {
+    file_name: String,
+    is_primary: bool,
+}
+
+impl Diagnostic {
+    /// The name of the clippy lint (like `needless_return`), if it's in the solution
+    fn clippy_lint_in_solution(&self) -> Option<&str> {
+        let lint = self.code.as_ref()?.code.strip_prefix("clippy::")?;
+        self.spans
+            .iter()
+            .any(|i| i.is_primary && i.file_name.ends_with(SOLUTION_FILE))
+            .then_some(lint)
+    }
+}
+
+fn clippy_failures(message: &str) -> Vec<TestCase> {
+    CLIPPY_GROUPS
+        .iter()
+        .map(|group| TestCase {
+            name: format!("clippy::{group}"),
+            placeholder: "".to_string(),
+            failure: Some(Failure {
+                message: message.to_string(),
+            }),
+        })
+        .collect()
+}
+
+/// Reads the groups of clippy lints from the output of `clippy-driver -W help`,
+/// where every group is a line with its name and comma-separated lints
+fn parse_lint_groups(help: &str) -> HashMap<String, &'static str> {
+    let mut groups = HashMap::new();
+    for line in help.lines() {
+        let Some((group, lints)) = line.trim().split_once(char::is_whitespace) else {
+            continue;
+        };
+        let Some(group) = CLIPPY_GROUPS
+            .iter()
+            .find(|i| group.strip_prefix("clippy::") == Some(i))
+        else {
+            continue;
+        };
+
+        for lint in lints.split(',') {
+            if let Some(lint) = lint.trim().strip_prefix("clippy::") {
+                groups.insert(lint.replace('-', "_"), *group);
+            }
+        }
+    }
+
+    groups
+}
+
+/// Which of the graded groups each clippy lint (like `needless_return`) is in,
+/// from the list of lints clippy prints
+fn lint_groups() -> Result<HashMap<String, &'static str>, String> {
+    let output = Command::new("clippy-driver")
+        .arg("-W")
+        .arg("help")
+        .output()
+        .map_err(|e| format!("could not list the lints of clippy: {e}"))?;
+    let groups = parse_lint_groups(&String::from_utf8_lossy(&output.stdout));
+
+    if groups.is_empty() {
+        return Err(format!(
+            "could not list the lints of clippy:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(groups)
+}
+
+/// Runs clippy once, and reports the lints of each group as a test case.
+/// The graded groups are passed with `--force-warn`, so `#[allow(...)]` in
+/// the solution can't hide them.
+fn clippy() -> Vec<TestCase> {
+    let groups = match lint_groups() {
+        Ok(i) => i,
+        Err(e) => return clippy_failures(&e),
+    };
+
+    let mut command = Command::new("cargo");
+    command
+        .arg("clippy")
+        .arg("--offline")
+        .arg("--message-format=json")
+        .arg("--");
+    for group in CLIPPY_GROUPS {
+        command.arg("--force-warn").arg(format!("clippy::{group}"));
+    }
+
+    let output = match command.output() {
+        Ok(i) => i,
+        Err(e) => return clippy_failures(&format!("could not run clippy: {e}")),
+    };
+
+    let diagnostics = output
+        .stdout
+        .split(|i| i == &b'\n')
+        .filter_map(|i| serde_json::from_slice(i).ok())
+        .filter_map(|i| match i {
+            CargoMessage::CompilerMessage { message } => Some(message),
+            CargoMessage::Other => None,
+        })
+        .collect::<Vec<_>>();
+
+    if !output.status.success() {
+        let errors = diagnostics
+            .iter()
+            .filter(|i| i.level == "error")
+            .filter_map(|i| i.rendered.as_deref())
+            .collect::<Vec<_>>();
+        return clippy_failures(&format!(
+            "the solution doesn't compile:\n{}{}",
+            errors.join("\n"),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let mut lints = HashMap::<&str, Vec<&str>>::new();
+    for diagnostic in &diagnostics {
+        let group = diagnostic
+            .clippy_lint_in_solution()
+            .and_then(|i| groups.get(i));
+        if let (Some(group), Some(rendered)) = (group, &diagnostic.rendered) {
+            lints.entry(group).or_default().push(rendered);
+        }
+    }
+
+    CLIPPY_GROUPS
+        .iter()
+        .map(|group| TestCase {
+            name: format!("clippy::{group}"),
+            placeholder: "".to_string(),
+            failure: lints.get(group).map(|lints| Failure {
+                message: lints.join("\n"),
+            }),
+        })
+        .collect()
+}
+
+fn rustfmt() -> TestCase {
+    let output = Command::new("rustfmt")
+        .arg("--check")
+        .arg("--edition")
+        .arg("2021")
+        .arg(SOLUTION_FILE)
+        .output();
+
+    TestCase {
+        name: "rustfmt".to_string(),
+        placeholder: "".to_string(),
+        failure: match output {
+            Ok(i) if i.status.success() => None,
+            Ok(i) => Some(Failure {
+                message: format!(
+                    "the solution is not formatted with rustfmt:\n{}{}",
+                    String::from_utf8_lossy(&i.stdout),
+                    String::from_utf8_lossy(&i.stderr)
+                ),
+            }),
+            Err(e) => Some(Failure {
+                message: format!("could not run rustfmt: {e}"),
+            }),
+        },
+    }
+}
+
+pub fn run_checks(checks: &[StyleCheck]) -> TestSuite {
+    let mut testcases = Vec::new();
+
+    for check in checks {
+        match check {
+            StyleCheck::Clippy => testcases.extend(clippy()),
+            StyleCheck::Rustfmt => testcases.push(rustfmt()),
+        }
+    }
+
+    TestSuite { testcases }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lint_groups() {
+        let help = "
+                clippy::needless-return  warn     using a return statement like `return expr;`
+
+Lint groups loaded by this crate:
+
+                   name  sub-lints
+                   ----  ---------
+            clippy::all  clippy::needless-return, clippy::ptr-arg, clippy::approx-constant
+    clippy::correctness  clippy::approx-constant
+       clippy::pedantic  clippy::cast-lossless
+          clippy::style  clippy::needless-return, clippy::ptr-arg
+";
+        let groups = parse_lint_groups(help);
+        assert_eq!(groups.get("needless_return"), Some(&"style"));
+        assert_eq!(groups.get("ptr_arg"), Some(&"style"));
+        assert_eq!(groups.get("approx_constant"), Some(&"correctness"));
+        assert_eq!(groups.get("cast_lossless"), None);
+    }
+
+    #[test]
+    fn lints_in_solution() {
+        let diagnostic = |code: &str, file: &str| {
+            serde_json::from_str::<Diagnostic>(&format!(
+                r#"{{"rendered": null, "level": "warning", "code": {{"code": "{code}"}},
+                    "spans": [{{"file_name": "{file}", "is_primary": true}}]}}"#
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(
+            diagnostic("clippy::ptr_arg", "src/solution.rs").clippy_lint_in_solution(),
+            Some("ptr_arg")
+        );
+        assert_eq!(
+            diagnostic("clippy::ptr_arg", "src/test.rs").clippy_lint_in_solution(),
+            None
+        );
+        assert_eq!(
+            diagnostic("unused_variables", "src/solution.rs").clippy_lint_in_solution(),
+            None
+        );
+    }
+}
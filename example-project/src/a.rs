@@ -13,6 +13,12 @@ mod assignment {
             println!("main!");
             // todo!()
         }
+
+        /// Students implement this one, the template only contains `todo!()`
+        #[weblab(stub)]
+        pub fn double(x: u32) -> u32 {
+            x * 2
+        }
     }
 
     #[weblab(test)]
@@ -23,6 +29,12 @@ mod assignment {
         fn test() {
             solution::main();
         }
+
+        // Regular comments (like this one) also end up in the files students download
+        #[test]
+        fn test_double() {
+            assert_eq!(solution::double(3), 6);
+        }
     }
 
     #[weblab(library)]
@@ -0,0 +1,13 @@
+use weblab::cloze_question;
+
+cloze_question! {
+    title: "example cloze",
+    question:
+    /// Fill in the blanks. A blank can accept multiple answers.
+    ///
+    /// To borrow a value mutably, write [[&mut]] in front of it.
+    /// A `Box<T>` stores its value on the [[heap]], and the
+    /// memory is freed when the box is [[dropped | freed]].
+    explanation:
+    /// Markdown works here too
+}
@@ -0,0 +1,19 @@
+use weblab::compiles_question;
+
+compiles_question! {
+    title: "example compiles",
+    question:
+    /// Does this compile?
+    code: {
+        fn longest(a: &str, b: &str) -> &str {
+            if a.len() > b.len() {
+                a
+            } else {
+                b
+            }
+        }
+    },
+    expect: error E0106,
+    explanation:
+    /// The returned reference needs a lifetime, since rust can't tell whether it borrows from `a` or `b`.
+}
@@ -0,0 +1,33 @@
+use weblab::weblab;
+
+// Long assignment texts are easier to write (and preview) in a markdown
+// file. The path is relative to the crate root (where Cargo.toml is).
+// Images used in the text need to be added as assets, so they're exported too.
+#[weblab(programming_assignment)]
+#[weblab(title = "description from a file")]
+#[weblab(description_file = "questions/sum.md")]
+#[weblab(asset = "questions/img/sum.svg")]
+mod assignment {
+    #[weblab(solution)]
+    mod solution {
+        #[weblab(stub)]
+        pub fn sum(xs: &[u64]) -> u64 {
+            xs.iter().sum()
+        }
+    }
+
+    #[weblab(test)]
+    mod test {
+        use super::solution::sum;
+
+        #[test]
+        fn empty() {
+            assert_eq!(sum(&[]), 0);
+        }
+
+        #[test]
+        fn some_numbers() {
+            assert_eq!(sum(&[1, 2, 3]), 6);
+        }
+    }
+}
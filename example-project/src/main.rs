@@ -2,18 +2,42 @@ use weblab::{weblab_folder, weblab_main};
 
 mod a;
 mod c;
+mod cloze_q_example;
+mod compiles_q_example;
+mod description_file;
+mod matching_q_example;
 mod mc_q_example;
+mod numeric_q_example;
 mod open_q_example;
+mod open_q_file_example;
+mod ordering_q_example;
+mod performance;
+mod pool;
+mod predict_output_q_example;
 mod question_list;
+mod split;
+mod true_false_q_example;
 
 weblab_folder! {
     "test",
 
     a,
     open_q_example,
+    open_q_file_example,
     mc_q_example,
+    numeric_q_example,
+    true_false_q_example,
+    cloze_q_example,
+    predict_output_q_example,
+    compiles_q_example,
+    matching_q_example,
+    ordering_q_example,
     c,
     question_list,
+    performance,
+    pool,
+    description_file,
+    split,
 }
 
 fn main() {
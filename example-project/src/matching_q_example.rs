@@ -0,0 +1,14 @@
+use weblab::matching_question;
+
+matching_question! {
+    title: "example matching",
+    question:
+    /// Match every trait with the method it requires
+    explanation:
+    /// Markdown works here too
+
+    pair "Iterator" => "next",
+    pair "Display" => "fmt",
+    pair "Clone" => "clone",
+    pair "Drop" => "drop",
+}
@@ -11,8 +11,14 @@ mc_question! {
 
     option "not" correct,
     option "a bit" correct,
-    option "lots",
+    option
+    /// ```rust
+    /// // options can contain markdown too
+    /// let x = 5;
+    /// ```
+    option "lots" feedback:
+    /// It's really not that much
 
-    expect 1 answers,
+    expect 2 answers,
     randomize
 }
@@ -0,0 +1,14 @@
+use weblab::numeric_question;
+
+numeric_question! {
+    title: "example numeric",
+    question:
+    /// How many bytes does a `(u8, u32)` occupy?
+    ///
+    /// # Markdown works here too
+    explanation:
+    /// The `u32` is aligned to 4 bytes, so 3 bytes of padding follow the `u8`.
+
+    answer: 8,
+    tolerance: 0,
+}
@@ -0,0 +1,10 @@
+use weblab::open_question;
+
+open_question! {
+    title: "question from a file",
+    // works for mc_question! too
+    question_file: "questions/explain.md",
+
+    answer:
+    /// Borrowing the slice is enough to read the numbers
+}
@@ -0,0 +1,14 @@
+use weblab::ordering_question;
+
+ordering_question! {
+    title: "example ordering",
+    question:
+    /// Put the steps of binary search in order
+    explanation:
+    /// Markdown works here too
+
+    step "look at the middle element",
+    step "compare it with the element you're searching for",
+    step "continue in the half that may contain the element",
+    step "stop when the element is found or the range is empty",
+}
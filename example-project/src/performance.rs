@@ -0,0 +1,46 @@
+use weblab::weblab;
+
+#[weblab(programming_assignment)]
+/// Sum all numbers from 1 up to and including `n`. Try to do this in constant time!
+#[weblab(title = "performance")]
+mod assignment {
+    #[weblab(solution)]
+    mod solution {
+        #[allow(unused_imports)]
+        use weblab::{solution_only, template_only};
+
+        pub fn sum_up_to(n: u64) -> u64 {
+            solution_only! {
+                n * (n + 1) / 2
+            }
+            template_only! {
+                (1..=n).sum()
+            }
+        }
+    }
+
+    #[weblab(test)]
+    mod test {
+        use super::solution;
+
+        #[test]
+        fn small() {
+            assert_eq!(solution::sum_up_to(10), 55);
+        }
+
+        // Performance tests are timed on weblab, and compared to the time the
+        // reference solution takes. This one fails when the student's solution
+        // is more than 3 times slower than the reference solution.
+        // Without a number (`#[weblab(performance)]`), at most 2 times is allowed.
+        #[test]
+        #[weblab(performance = 3)]
+        fn fast() {
+            for i in 0..1000 {
+                assert_eq!(
+                    solution::sum_up_to(100_000 + i),
+                    (100_000 + i) * (100_001 + i) / 2
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use weblab::question_pool;
+
+mod copy {
+    use weblab::true_false_question;
+
+    true_false_question! {
+        title: "copy",
+        question:
+        /// `i32` implements `Copy`.
+
+        answer: true,
+    }
+}
+
+mod string_copy {
+    use weblab::true_false_question;
+
+    true_false_question! {
+        title: "string copy",
+        question:
+        /// `String` implements `Copy`.
+        explanation:
+        /// Copying a `String` would have to copy its heap allocation too, so it's only `Clone`.
+
+        answer: false,
+    }
+}
+
+mod reference_copy {
+    use weblab::true_false_question;
+
+    true_false_question! {
+        title: "reference copy",
+        question:
+        /// `&mut i32` implements `Copy`.
+        explanation:
+        /// Copying a mutable reference would allow two mutable references to the same value.
+
+        answer: false,
+    }
+}
+
+question_pool! {
+    "example pool",
+    pick 2 of
+
+    copy,
+    string_copy,
+    reference_copy,
+}
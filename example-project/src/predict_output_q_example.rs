@@ -0,0 +1,17 @@
+use weblab::predict_output_question;
+
+predict_output_question! {
+    title: "example predict output",
+    question:
+    /// What does this program print?
+    code: r#"
+fn main() {
+    let mut v = vec![3, 1, 2];
+    // sorting happens in place
+    v.sort();
+    for i in v.iter().rev() {
+        println!("{i}");
+    }
+}
+"#
+}
@@ -17,7 +17,7 @@ inline_question_list! {
         option "a bit" correct,
         option "lots",
 
-        expect 1 answers,
+        expect 2 answers,
         randomize
     }
 
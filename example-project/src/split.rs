@@ -0,0 +1,16 @@
+use weblab::weblab;
+
+/// # Maximum
+/// Find the largest number in a slice, or `None` if it's empty.
+///
+/// Larger assignments can be split over multiple files. The contents of
+/// the modules below are read from the files (relative to the crate root).
+#[weblab(programming_assignment)]
+#[weblab(title = "split over files")]
+mod assignment {
+    #[weblab(solution, file = "src/split/solution.rs")]
+    mod solution {}
+
+    #[weblab(test, file = "src/split/test.rs")]
+    mod test {}
+}
@@ -0,0 +1,4 @@
+#[weblab(stub)]
+pub fn maximum(xs: &[i64]) -> Option<i64> {
+    xs.iter().copied().max()
+}
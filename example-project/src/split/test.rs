@@ -0,0 +1,11 @@
+use super::solution::maximum;
+
+#[test]
+fn empty() {
+    assert_eq!(maximum(&[]), None);
+}
+
+#[test]
+fn negative_numbers() {
+    assert_eq!(maximum(&[-3, -1, -2]), Some(-1));
+}
@@ -0,0 +1,11 @@
+use weblab::true_false_question;
+
+true_false_question! {
+    title: "example true/false",
+    question:
+    /// A `String` can be borrowed as a `&str`.
+    explanation:
+    /// `String` implements `Deref<Target = str>`, so `&String` coerces to `&str`.
+
+    answer: true,
+}
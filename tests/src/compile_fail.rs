@@ -0,0 +1,134 @@
+//! Checks the errors of the assignments in weblab/src/compile_fail.rs. As
+//! doctests, those only have to fail to compile, so here they're compiled with
+//! rustc, and every error has to be on a line marked with
+//! `//~ ERROR <part of the message>`, like in rustc's own ui tests.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs};
+
+const DOCTESTS: &str = include_str!("../../weblab/src/compile_fail.rs");
+
+/// The code of every `compile_fail` doctest
+fn snippets() -> Vec<String> {
+    let mut res = Vec::new();
+    let mut snippet: Option<String> = None;
+
+    for line in DOCTESTS.lines() {
+        let line = line.strip_prefix("//!").unwrap_or(line);
+        let line = line.strip_prefix(' ').unwrap_or(line);
+        match &mut snippet {
+            None if line == "```compile_fail" => snippet = Some(String::new()),
+            None => {}
+            Some(code) if line == "```" => {
+                res.push(std::mem::take(code));
+                snippet = None;
+            }
+            Some(code) => {
+                code.push_str(line);
+                code.push('\n');
+            }
+        }
+    }
+
+    res
+}
+
+/// The line and (part of the) message of every error marked in `code`
+fn expected_errors(code: &str) -> Vec<(usize, String)> {
+    code.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let (_, message) = line.split_once("//~ ERROR ")?;
+            Some((i + 1, message.trim().to_string()))
+        })
+        .collect()
+}
+
+/// The newest build of weblab, next to the dependencies of this test
+fn weblab_rlib(deps: &Path) -> PathBuf {
+    fs::read_dir(deps)
+        .unwrap()
+        .map(|i| i.unwrap().path())
+        .filter(|i| {
+            i.file_name()
+                .and_then(|i| i.to_str())
+                .is_some_and(|i| i.starts_with("libweblab-") && i.ends_with(".rlib"))
+        })
+        .max_by_key(|i| i.metadata().and_then(|i| i.modified()).unwrap())
+        .expect("weblab should be built before its tests run")
+}
+
+/// Compiles `code` like rustdoc does, and returns the line and message of
+/// every error, together with everything rustc printed
+fn compile(name: &str, code: &str) -> (Vec<(usize, String)>, String) {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let dir = env::temp_dir().join(format!("weblab-compile-fail-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let source = dir.join(format!("{name}.rs"));
+    fs::write(&source, code).unwrap();
+
+    let output = Command::new("rustc")
+        .args(["--edition=2021", "--crate-type=lib", "--emit=metadata"])
+        .args(["--error-format=short", "-A", "unused"])
+        .arg("-L")
+        .arg(format!("dependency={}", deps.display()))
+        .arg("--extern")
+        .arg(format!("weblab={}", weblab_rlib(&deps).display()))
+        .arg("--out-dir")
+        .arg(&dir)
+        .arg(&source)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    // like `/tmp/.../snippet_0.rs:7:18: error: unknown weblab attribute ...`
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let prefix = format!("{}:", source.display());
+    let errors = stderr
+        .lines()
+        .filter_map(|i| {
+            let (line, rest) = i.strip_prefix(&prefix)?.split_once(':')?;
+            let (_, rest) = rest.split_once(": ")?;
+            let (level, message) = rest.split_once(": ")?;
+            level
+                .starts_with("error")
+                .then(|| (line.parse().unwrap(), message.to_string()))
+        })
+        .collect();
+
+    (errors, stderr)
+}
+
+#[test]
+fn error_messages() {
+    let snippets = snippets();
+    assert!(!snippets.is_empty());
+
+    for (i, code) in snippets.iter().enumerate() {
+        let (errors, stderr) = compile(&format!("snippet_{i}"), code);
+        let expected = expected_errors(code);
+        assert!(!expected.is_empty(), "no errors are marked in:\n{code}");
+
+        let matches = |(line, message): &(usize, String),
+                       (expected_line, part): &(usize, String)| {
+            line == expected_line && message.contains(part.as_str())
+        };
+        for e in &expected {
+            assert!(
+                errors.iter().any(|i| matches(i, e)),
+                "expected `{}` on line {} of:\n{code}\nbut got:\n{stderr}",
+                e.1,
+                e.0
+            );
+        }
+        for e in &errors {
+            assert!(
+                expected.iter().any(|i| matches(e, i)),
+                "unexpected error `{}` on line {} of:\n{code}\n{stderr}",
+                e.1,
+                e.0
+            );
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! The solution of `m_module_files`, in its own file
+
+#[weblab(stub)]
+pub fn product(xs: &[u64]) -> u64 {
+    // an empty product is 1
+    xs.iter().product()
+}
@@ -0,0 +1,10 @@
+#![allow(clippy::identity_op)]
+
+use super::solution::product;
+
+#[test]
+fn product_of_numbers() {
+    // 2 * 3 * 4
+    assert_eq!(product(&[2, 3, 4]), 24);
+    assert_eq!(product(&[1, 1]), 1 * 1);
+}
@@ -1,6 +1,9 @@
+#[cfg(test)]
+mod compile_fail;
+
 #[cfg(test)]
 mod tests {
-    use weblab::weblab_assignment_info;
+    use weblab::{weblab_assignment_info, CompileResult, ForbiddenConstruct, WeblabAssignment};
 
     mod m_simple_conversion {
         use weblab::weblab;
@@ -36,6 +39,555 @@ mod tests {
         }
     }
 
+    mod m_performance {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        mod perf {
+            #[weblab(solution)]
+            mod solution {
+                pub fn answer() -> u32 {
+                    42
+                }
+            }
+
+            #[weblab(test)]
+            mod test {
+                use super::solution;
+
+                #[test]
+                #[weblab(performance = 1.5)]
+                fn fast() {
+                    assert_eq!(solution::answer(), 42);
+                }
+            }
+        }
+    }
+
+    mod m_style_checks {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        #[weblab(lint = "clippy", require_fmt)]
+        #[weblab(forbid(unsafe, "std::collections::HashMap", "println!"))]
+        mod style {
+            #[weblab(solution)]
+            mod solution {}
+
+            #[weblab(test)]
+            mod test {}
+        }
+    }
+
+    mod m_numeric {
+        use weblab::numeric_question;
+
+        numeric_question! {
+            title: "size",
+            question:
+            /// How many bytes does a `u64` occupy?
+
+            answer: 8,
+            tolerance: 0.5,
+        }
+    }
+
+    mod m_true_false {
+        use weblab::true_false_question;
+
+        true_false_question! {
+            title: "borrowing",
+            question:
+            /// A value can have multiple mutable references at the same time.
+            explanation:
+            /// Only one mutable reference can exist at a time.
+
+            answer: false,
+        }
+    }
+
+    mod m_cloze {
+        use weblab::cloze_question;
+
+        cloze_question! {
+            title: "vec",
+            question:
+            /// A `Vec<T>` stores its elements on the [[heap]].
+            /// Its length is returned by [[len | len()]].
+        }
+    }
+
+    mod m_predict_output {
+        use weblab::predict_output_question;
+
+        predict_output_question! {
+            title: "shadowing",
+            question:
+            /// What does this program print?
+            code: {
+                fn main() {
+                    let x = 1;
+                    let x = x + 1;
+                    println!("{}", x);
+                }
+            }
+        }
+    }
+
+    mod m_compiles {
+        use weblab::compiles_question;
+
+        compiles_question! {
+            title: "moved",
+            question:
+            /// Does this compile?
+            code: {
+                fn main() {
+                    let s = String::new();
+                    let t = s;
+                    println!("{}", s);
+                }
+            },
+            expect: error E0382,
+        }
+    }
+
+    mod m_compiles_any_error {
+        use weblab::compiles_question;
+
+        compiles_question! {
+            title: "any error",
+            question:
+            /// Does this compile?
+            code: {
+                fn main() {
+                    let x: u32 = "five";
+                }
+            },
+            expect: error,
+        }
+    }
+
+    mod m_matching {
+        use weblab::matching_question;
+
+        matching_question! {
+            title: "traits",
+            question:
+            /// Match every trait with its method
+
+            pair "Iterator" => "next",
+            pair "Display" => "fmt",
+            pair "Clone" => "clone",
+        }
+    }
+
+    mod m_ordering {
+        use weblab::ordering_question;
+
+        ordering_question! {
+            title: "insert",
+            question:
+            /// Put the steps of inserting into a hash map in order
+
+            step "hash the key",
+            step "find the bucket",
+            step "store the entry",
+        }
+    }
+
+    mod m_mc_feedback {
+        use weblab::mc_question;
+
+        mc_question! {
+            title: "feedback",
+            question:
+            /// What is `2 + 2`?
+
+            option "4" correct,
+            option "5" feedback:
+            /// That's one too many
+            option "22",
+        }
+    }
+
+    mod m_mc_markdown {
+        use weblab::mc_question;
+
+        mc_question! {
+            title: "markdown",
+            question:
+            /// Which of these compiles?
+
+            option
+            /// ```rust
+            /// fn main() {
+            ///     let x = 5;
+            /// }
+            /// ```
+            correct,
+            option "`let 5 = x;`",
+        }
+    }
+
+    mod m_question_file {
+        use weblab::mc_question;
+
+        mc_question! {
+            title: "question file",
+            question_file: "questions/question.md",
+
+            option "apple" correct,
+            option "carrot",
+        }
+    }
+
+    mod m_indented {
+        use weblab::open_question;
+
+        open_question! {
+            title: "indented",
+            question:
+            #[doc = " \u{a0}\u{a0}indented with non-breaking spaces"]
+            #[doc = "   - a nested list"]
+            #[doc = ""]
+            #[doc = " \u{3000}wide"]
+        }
+    }
+
+    mod m_image_file {
+        use weblab::open_question;
+
+        open_question! {
+            title: "image in a file",
+            question_file: "questions/diagram.md",
+            asset: "questions/diagram.svg",
+        }
+    }
+
+    mod m_description_file {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        #[weblab(description_file = "questions/description.md")]
+        mod description_file {
+            #[weblab(solution)]
+            mod solution {}
+
+            #[weblab(test)]
+            mod test {}
+        }
+    }
+
+    mod m_assets {
+        use weblab::true_false_question;
+
+        true_false_question! {
+            title: "assets",
+            question:
+            /// ![a diagram](questions/diagram.svg)
+            /// The diagram adds up to 6
+            asset: "questions/diagram.svg",
+            answer: true,
+        }
+    }
+
+    mod m_module_files {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        #[weblab(title = "module files")]
+        mod assignment {
+            #[weblab(solution, file = "src/files/solution.rs")]
+            mod solution {}
+
+            #[weblab(test, file = "src/files/test.rs")]
+            mod test {}
+        }
+    }
+
+    mod m_libraries {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        #[weblab(title = "libraries")]
+        mod assignment {
+            #[weblab(solution)]
+            mod solution {
+                use super::area::area;
+                use super::shapes::Square;
+
+                pub fn total(squares: &[Square]) -> u32 {
+                    squares.iter().map(area).sum()
+                }
+            }
+
+            #[weblab(test)]
+            mod test {
+                use super::shapes::Square;
+                use super::solution::total;
+
+                #[test]
+                fn two_squares() {
+                    assert_eq!(total(&[Square(2), Square(3)]), 13);
+                }
+            }
+
+            #[weblab(library)]
+            mod shapes {
+                pub struct Square(pub u32);
+            }
+
+            #[weblab(library)]
+            mod area {
+                use super::shapes::Square;
+
+                pub fn area(s: &Square) -> u32 {
+                    s.0 * s.0
+                }
+            }
+        }
+    }
+
+    mod m_performance_libraries {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        mod perf {
+            #[weblab(solution)]
+            mod solution {
+                use super::shapes::Square;
+
+                pub fn total(squares: &[Square]) -> u32 {
+                    squares.iter().map(super::area::area).sum()
+                }
+            }
+
+            #[weblab(test)]
+            mod test {
+                use super::shapes::Square;
+                use super::solution;
+
+                #[test]
+                #[weblab(performance)]
+                fn fast() {
+                    assert_eq!(solution::total(&[Square(2), Square(3)]), 13);
+                }
+            }
+
+            #[weblab(library)]
+            mod shapes {
+                pub struct Square(pub u32);
+            }
+
+            #[weblab(library)]
+            mod area {
+                use super::shapes::Square;
+
+                pub fn area(s: &Square) -> u32 {
+                    s.0 * s.0
+                }
+            }
+        }
+    }
+
+    mod m_performance_imports {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        mod perf {
+            #[weblab(solution)]
+            mod solution {
+                pub fn answer() -> u32 {
+                    42
+                }
+            }
+
+            #[weblab(test)]
+            mod test {
+                use super::solution::answer as a;
+                use super::solution::*;
+
+                #[test]
+                #[weblab(performance)]
+                fn fast() {
+                    assert_eq!(answer(), a());
+                }
+            }
+        }
+    }
+
+    mod m_pool {
+        use super::{m_numeric, m_true_false};
+        use weblab::question_pool;
+
+        question_pool! {
+            "pool",
+            pick 1 of
+
+            m_numeric,
+            m_true_false,
+        }
+    }
+
+    mod m_named_pool {
+        use super::{m_numeric, m_true_false};
+        use weblab::question_pool;
+
+        question_pool! {
+            named_pool,
+            pick 2 of
+
+            m_numeric,
+            m_true_false,
+        }
+    }
+
+    mod m_named_folder {
+        use super::{m_numeric, m_pool};
+        use weblab::weblab_folder;
+
+        weblab_folder! {
+            named_folder,
+
+            m_numeric,
+            m_pool,
+        }
+    }
+
+    mod m_only_in_expressions {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        mod only {
+            #[weblab(solution)]
+            mod solution {
+                #[allow(unused_imports)]
+                use weblab::{solution_only, template_only};
+
+                pub struct Counter(u32);
+
+                impl Counter {
+                    solution_only! {
+                        fn step() -> u32 {
+                            2
+                        }
+                    }
+
+                    pub fn next(&mut self) -> u32 {
+                        self.0 += solution_only! { Self::step() };
+                        self.0 * solution_only! { 1 + 1 }
+                    }
+                }
+
+                pub fn double(x: u32) -> u32 {
+                    solution_only! { x * 2 }
+                }
+            }
+
+            #[weblab(test)]
+            mod test {}
+        }
+    }
+
+    mod m_nested_only {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        mod nested {
+            #[weblab(solution)]
+            mod solution {
+                #[allow(unused_imports)]
+                use weblab::{solution_only, template_only};
+
+                template_only! {
+                    pub fn double(x: u32) -> u32 {
+                        let doubled = solution_only! { x * 2 };
+                        doubled
+                    }
+                }
+                solution_only! {
+                    pub fn double(x: u32) -> u32 {
+                        x + x
+                    }
+                }
+            }
+
+            #[weblab(test)]
+            mod test {
+                #[allow(unused_imports)]
+                use weblab::solution_only;
+
+                use super::solution;
+
+                solution_only! {
+                    #[test]
+                    #[weblab(performance)]
+                    fn fast() {
+                        assert_eq!(solution::double(2), 4);
+                    }
+                }
+            }
+        }
+    }
+
+    mod m_stub {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        mod stub {
+            #[weblab(solution)]
+            mod solution {
+                /// Adds up all numbers
+                #[weblab(stub)]
+                pub fn sum(xs: &[u32]) -> u32 {
+                    xs.iter().sum()
+                }
+
+                pub struct Stack(Vec<u32>);
+
+                impl Stack {
+                    #[weblab(stub = "None")]
+                    pub fn pop(&mut self) -> Option<u32> {
+                        self.0.pop()
+                    }
+                }
+            }
+
+            #[weblab(test)]
+            mod test {}
+        }
+    }
+
+    mod m_comments {
+        use weblab::weblab;
+
+        #[weblab(programming_assignment)]
+        mod comments {
+            #[weblab(solution)]
+            mod solution {
+                #[allow(unused_imports)]
+                use weblab::solution_only;
+
+                // Start by thinking about the base case
+                pub fn factorial(n: u64) -> u64 {
+                    solution_only! {
+                        if n == 0 {
+                            return 1;
+                        }
+                    }
+
+                    /* and then
+                    the rest */
+                    n * solution_only! { factorial(n - 1) }
+                }
+            }
+
+            #[weblab(test)]
+            mod test {}
+        }
+    }
+
     #[test]
     fn simple_conversion() {
         let a = weblab_assignment_info!(m_simple_conversion);
@@ -48,4 +600,423 @@ mod tests {
         let a = weblab_assignment_info!(m_no_title);
         assert_eq!(a.title(), "aaa");
     }
+
+    #[test]
+    fn numeric() {
+        if let WeblabAssignment::Numeric(n) = weblab_assignment_info!(m_numeric) {
+            assert_eq!(n.title, "size");
+            assert_eq!(n.answer, 8.0);
+            assert_eq!(n.tolerance, 0.5);
+        } else {
+            panic!("expected a numeric question");
+        }
+    }
+
+    #[test]
+    fn true_false() {
+        if let WeblabAssignment::TrueFalse(q) = weblab_assignment_info!(m_true_false) {
+            assert_eq!(q.title, "borrowing");
+            assert!(!q.answer);
+            assert_eq!(
+                q.explanation,
+                "Only one mutable reference can exist at a time."
+            );
+        } else {
+            panic!("expected a true/false question");
+        }
+    }
+
+    #[test]
+    fn cloze() {
+        if let WeblabAssignment::Cloze(q) = weblab_assignment_info!(m_cloze) {
+            assert_eq!(
+                q.assignment_text,
+                "A `Vec<T>` stores its elements on the [[1]].\nIts length is returned by [[2]]."
+            );
+            assert_eq!(q.blanks.len(), 2);
+            assert_eq!(q.blanks[0].answers, ["heap"]);
+            assert_eq!(q.blanks[1].answers, ["len", "len()"]);
+        } else {
+            panic!("expected a cloze question");
+        }
+    }
+
+    #[test]
+    fn predict_output() {
+        if let WeblabAssignment::PredictOutput(q) = weblab_assignment_info!(m_predict_output) {
+            assert_eq!(q.assignment_text, "What does this program print?");
+            assert!(q.code.starts_with("fn main"));
+            assert!(q.code.contains("println"));
+        } else {
+            panic!("expected a predict-the-output question");
+        }
+    }
+
+    #[test]
+    fn compiles() {
+        if let WeblabAssignment::Compiles(q) = weblab_assignment_info!(m_compiles) {
+            assert!(q.code.starts_with("fn main"));
+            assert!(matches!(q.expected, CompileResult::Error("E0382")));
+        } else {
+            panic!("expected a does-this-compile question");
+        }
+    }
+
+    #[test]
+    fn compiles_any_error() {
+        if let WeblabAssignment::Compiles(q) = weblab_assignment_info!(m_compiles_any_error) {
+            assert!(matches!(q.expected, CompileResult::Fails));
+        } else {
+            panic!("expected a does-this-compile question");
+        }
+    }
+
+    #[test]
+    fn matching() {
+        if let WeblabAssignment::Matching(q) = weblab_assignment_info!(m_matching) {
+            assert_eq!(q.pairs.len(), 3);
+            assert_eq!(q.pairs[1].left, "Display");
+            assert_eq!(q.pairs[1].right, "fmt");
+        } else {
+            panic!("expected a matching question");
+        }
+    }
+
+    #[test]
+    fn ordering() {
+        if let WeblabAssignment::Ordering(q) = weblab_assignment_info!(m_ordering) {
+            assert_eq!(
+                q.steps,
+                ["hash the key", "find the bucket", "store the entry"]
+            );
+        } else {
+            panic!("expected an ordering question");
+        }
+    }
+
+    #[test]
+    fn mc_feedback() {
+        if let WeblabAssignment::MultipleChoice(q) = weblab_assignment_info!(m_mc_feedback) {
+            assert_eq!(q.options.len(), 3);
+            assert!(q.options[0].is_correct);
+            assert_eq!(q.options[0].feedback, "");
+            assert_eq!(q.options[1].feedback, "That's one too many");
+            assert_eq!(q.options[2].text, "22");
+        } else {
+            panic!("expected a multiple choice question");
+        }
+    }
+
+    #[test]
+    fn mc_markdown() {
+        if let WeblabAssignment::MultipleChoice(q) = weblab_assignment_info!(m_mc_markdown) {
+            assert_eq!(
+                q.options[0].text,
+                "```rust\nfn main() {\n    let x = 5;\n}\n```"
+            );
+            assert!(q.options[0].is_correct);
+            assert_eq!(q.options[1].text, "`let 5 = x;`");
+        } else {
+            panic!("expected a multiple choice question");
+        }
+    }
+
+    #[test]
+    fn pool() {
+        if let WeblabAssignment::Pool(p) = weblab_assignment_info!(m_pool) {
+            assert_eq!(p.title, "pool");
+            assert_eq!(p.pick, 1);
+            assert_eq!(p.assignments.len(), 2);
+            assert_eq!(p.assignments[1].title(), "borrowing");
+        } else {
+            panic!("expected a question pool");
+        }
+    }
+
+    #[test]
+    fn named_by_ident() {
+        if let WeblabAssignment::Pool(p) = weblab_assignment_info!(m_named_pool) {
+            assert_eq!(p.title, "named_pool");
+            assert_eq!(p.pick, 2);
+            assert_eq!(p.assignments.len(), 2);
+        } else {
+            panic!("expected a question pool");
+        }
+
+        if let WeblabAssignment::Folder(f) = weblab_assignment_info!(m_named_folder) {
+            assert_eq!(f.title, "named_folder");
+            assert_eq!(f.assignments.len(), 2);
+            assert_eq!(f.assignments[1].title(), "pool");
+        } else {
+            panic!("expected a folder");
+        }
+    }
+
+    #[test]
+    fn only_in_expressions() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_only_in_expressions) {
+            assert!(!p.solution.contains("solution_only"));
+            assert!(p.solution.contains("fn step"));
+            assert!(p.solution.contains("(1 + 1)"));
+            assert!(p.solution.contains("x * 2"));
+
+            assert!(!p.solution_template.contains("solution_only"));
+            assert!(!p.solution_template.contains("fn step"));
+            assert!(p.solution_template.contains("todo"));
+            let double = p.solution_template.split_whitespace().collect::<String>();
+            assert!(double.contains("fndouble(x:u32)->u32{todo!()}"));
+        } else {
+            panic!("expected a programming assignment");
+        }
+    }
+
+    #[test]
+    fn nested_only() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_nested_only) {
+            assert!(p.solution.contains("x + x"));
+            assert!(!p.solution.contains("x * 2"));
+            assert!(!p.solution.contains("only"));
+
+            assert!(p.solution_template.contains("let doubled"));
+            assert!(p.solution_template.contains("todo"));
+            assert!(!p.solution_template.contains("x * 2"));
+            assert!(!p.solution_template.contains("only"));
+
+            assert!(p.test.contains("__weblab_performance"));
+            assert!(!p.test_template.contains("fn fast"));
+        } else {
+            panic!("expected a programming assignment");
+        }
+    }
+
+    #[test]
+    fn comments() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_comments) {
+            assert!(p
+                .solution
+                .contains("// Start by thinking about the base case"));
+            assert!(p.solution.contains("/* and then\n"));
+
+            assert!(p
+                .solution_template
+                .contains("// Start by thinking about the base case"));
+            assert!(p.solution_template.contains("the rest */"));
+            assert!(!p.solution_template.contains("factorial(n - 1)"));
+            assert!(p.solution_template.contains("n * todo"));
+        } else {
+            panic!("expected a programming assignment");
+        }
+    }
+
+    #[test]
+    fn indentation() {
+        // only the space all lines start with is removed
+        assert_eq!(
+            weblab_assignment_info!(m_indented).assignment_text(),
+            "\u{a0}\u{a0}indented with non-breaking spaces\n  - a nested list\n\n\u{3000}wide"
+        );
+    }
+
+    #[test]
+    fn files() {
+        assert_eq!(
+            weblab_assignment_info!(m_question_file).assignment_text(),
+            "Which of these is a **fruit**?"
+        );
+        assert_eq!(
+            weblab_assignment_info!(m_description_file).assignment_text(),
+            "# Description\n\nFrom a *file*"
+        );
+    }
+
+    #[test]
+    fn images_relative_to_files() {
+        // made relative to the crate root, like the path of the asset
+        assert_eq!(
+            weblab_assignment_info!(m_image_file).assignment_text(),
+            "![a diagram](questions/diagram.svg \"the diagram\") adds up to 6, and so does\n\
+            `![not an image](diagram.svg)` in code."
+        );
+    }
+
+    #[test]
+    fn assets() {
+        let a = weblab_assignment_info!(m_assets);
+        assert_eq!(a.assets().len(), 1);
+        assert_eq!(a.assets()[0].path, "questions/diagram.svg");
+        assert_eq!(
+            a.assets()[0].contents,
+            include_bytes!("../questions/diagram.svg")
+        );
+    }
+
+    #[test]
+    fn module_files() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_module_files) {
+            assert!(p
+                .solution
+                .starts_with("//! The solution of `m_module_files`"));
+            assert!(p
+                .solution
+                .contains("    // an empty product is 1\n    xs.iter().product()"));
+            assert!(!p.solution.contains("weblab"));
+            assert!(p
+                .solution_template
+                .contains("//! The solution of `m_module_files`"));
+            assert!(p
+                .solution_template
+                .contains("pub fn product(xs: &[u64]) -> u64 {"));
+            assert!(p.solution_template.replace(' ', "").contains("{todo!()}"));
+            assert!(!p.solution_template.contains("weblab"));
+            assert!(p.test.starts_with("#![allow(clippy::identity_op)]"));
+            assert!(p
+                .test
+                .contains("fn product_of_numbers() {\n    // 2 * 3 * 4"));
+        } else {
+            panic!("expected programming assignment")
+        }
+    }
+
+    #[test]
+    fn libraries() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_libraries) {
+            let names = p.library.iter().map(|i| i.name).collect::<Vec<_>>();
+            assert_eq!(names, ["shapes", "area"]);
+            assert!(p.library[0].contents.contains("pub struct Square"));
+            assert!(p.library[1].contents.contains("pub fn area"));
+        } else {
+            panic!("expected programming assignment")
+        }
+
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_simple_conversion) {
+            assert_eq!(p.library.len(), 1);
+            assert_eq!(p.library[0].name, "library");
+        } else {
+            panic!("expected programming assignment")
+        }
+    }
+
+    #[test]
+    fn stub() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_stub) {
+            assert!(p.solution.contains("xs.iter().sum()") || p.solution.contains("xs . iter ()"));
+            assert!(
+                p.solution.contains("self.0.pop()") || p.solution.contains("self . 0 . pop ()")
+            );
+            assert!(!p.solution.contains("weblab"));
+
+            assert!(p.solution_template.contains("Adds up all numbers"));
+            assert!(p.solution_template.contains("pub fn sum"));
+            assert!(p.solution_template.contains("todo"));
+            assert!(
+                !p.solution_template.contains("sum()") && !p.solution_template.contains("sum ()")
+            );
+            assert!(p.solution_template.contains("pub fn pop"));
+            assert!(
+                !p.solution_template.contains("pop()") && !p.solution_template.contains("pop ()")
+            );
+            assert!(p.solution_template.contains("None"));
+            assert!(!p.solution_template.contains("weblab"));
+        } else {
+            panic!("expected a programming assignment");
+        }
+    }
+
+    #[test]
+    fn performance() {
+        let a = weblab_assignment_info!(m_performance);
+        if let WeblabAssignment::Programming(p) = a {
+            assert!(p.test.contains("__weblab_performance"));
+            assert!(p.test.contains("__weblab_reference"));
+            assert!(!p.solution.contains("__weblab_reference"));
+        } else {
+            panic!("expected a programming assignment");
+        }
+    }
+
+    #[test]
+    fn performance_imports() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_performance_imports) {
+            let test = p.test.split_whitespace().collect::<String>();
+            assert!(test.contains("useself::__weblab_reference::solution::*;"));
+            assert!(test.contains("useself::__weblab_reference::solution::answerasa;"));
+        } else {
+            panic!("expected a programming assignment");
+        }
+    }
+
+    #[test]
+    fn performance_libraries() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_performance_libraries) {
+            // the same files as the runner makes from the assignment
+            let dir = std::env::temp_dir().join(format!(
+                "weblab-performance-libraries-{}",
+                std::process::id()
+            ));
+            std::fs::create_dir_all(&dir).unwrap();
+            let mut main = String::new();
+            for (name, contents) in p
+                .library
+                .iter()
+                .map(|i| (i.name, i.contents))
+                .chain([("solution", p.solution), ("test", p.test)])
+            {
+                std::fs::write(dir.join(format!("{name}.rs")), contents).unwrap();
+                main += &format!("mod {name};\n");
+            }
+            std::fs::write(dir.join("main.rs"), main + "fn main() {}\n").unwrap();
+
+            let output = std::process::Command::new("rustc")
+                .args(["--edition=2021", "--test", "-o"])
+                .arg(dir.join("main"))
+                .arg(dir.join("main.rs"))
+                .output()
+                .unwrap();
+            std::fs::remove_dir_all(&dir).unwrap();
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        } else {
+            panic!("expected a programming assignment");
+        }
+    }
+
+    #[test]
+    fn style_checks() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_style_checks) {
+            assert!(p.clippy);
+            assert!(p.require_fmt);
+            assert!(matches!(
+                p.forbidden,
+                [
+                    ForbiddenConstruct::Keyword("unsafe"),
+                    ForbiddenConstruct::Path("std::collections::HashMap"),
+                    ForbiddenConstruct::Macro("println"),
+                ]
+            ));
+        } else {
+            panic!("expected a programming assignment");
+        }
+
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_no_title) {
+            assert!(!p.clippy);
+            assert!(!p.require_fmt);
+            assert!(p.forbidden.is_empty());
+        } else {
+            panic!("expected a programming assignment");
+        }
+    }
+
+    #[test]
+    fn public_api() {
+        if let WeblabAssignment::Programming(p) = weblab_assignment_info!(m_performance) {
+            assert_eq!(p.api.len(), 1);
+            assert_eq!(p.api[0].path, "answer");
+            assert!(p.api[0].signature.starts_with("fn answer"));
+        } else {
+            panic!("expected a programming assignment");
+        }
+    }
 }
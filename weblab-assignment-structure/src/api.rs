@@ -0,0 +1,95 @@
+use quote::ToTokens;
+use syn::{File, ImplItem, Item, ItemImpl, Signature, Type, TypePath, Visibility};
+
+/// A public item of a solution. Students are expected to keep the public items
+/// of the solution template in their solution, since the spec tests may use them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicItem {
+    /// path relative to the solution, like `Stack::push`
+    pub path: String,
+    /// the signature of functions, or the kind and name of other items (like `struct Stack`)
+    pub signature: String,
+}
+
+fn is_pub(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+fn signature(sig: &Signature) -> String {
+    sig.to_token_stream().to_string()
+}
+
+/// The public items in `items`, including the ones in public modules and the
+/// public methods in (non-trait) `impl` blocks.
+pub fn public_api(items: &[Item]) -> Vec<PublicItem> {
+    let mut res = Vec::new();
+    collect_public_api(items, "", &mut res);
+    res
+}
+
+fn collect_public_api(items: &[Item], prefix: &str, res: &mut Vec<PublicItem>) {
+    for item in items {
+        let mut add = |name: String, signature: String| {
+            res.push(PublicItem {
+                path: format!("{prefix}{name}"),
+                signature,
+            })
+        };
+
+        match item {
+            Item::Fn(f) if is_pub(&f.vis) => add(f.sig.ident.to_string(), signature(&f.sig)),
+            Item::Struct(s) if is_pub(&s.vis) => {
+                add(s.ident.to_string(), format!("struct {}", s.ident))
+            }
+            Item::Enum(e) if is_pub(&e.vis) => {
+                add(e.ident.to_string(), format!("enum {}", e.ident))
+            }
+            Item::Trait(t) if is_pub(&t.vis) => {
+                add(t.ident.to_string(), format!("trait {}", t.ident))
+            }
+            Item::Type(t) if is_pub(&t.vis) => {
+                add(t.ident.to_string(), format!("type {}", t.ident))
+            }
+            Item::Const(c) if is_pub(&c.vis) => {
+                add(c.ident.to_string(), format!("const {}", c.ident))
+            }
+            Item::Static(s) if is_pub(&s.vis) => {
+                add(s.ident.to_string(), format!("static {}", s.ident))
+            }
+            Item::Impl(ItemImpl {
+                trait_: None,
+                self_ty,
+                items,
+                ..
+            }) => {
+                if let Type::Path(TypePath { qself: None, path }) = &**self_ty {
+                    if let Some(ty) = path.segments.last() {
+                        for i in items {
+                            if let ImplItem::Method(m) = i {
+                                if is_pub(&m.vis) {
+                                    add(
+                                        format!("{}::{}", ty.ident, m.sig.ident),
+                                        signature(&m.sig),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Item::Mod(m) if is_pub(&m.vis) => {
+                add(m.ident.to_string(), format!("mod {}", m.ident));
+                if let Some((_, items)) = &m.content {
+                    collect_public_api(items, &format!("{prefix}{}::", m.ident), res);
+                }
+            }
+            // the contents of template_only! blocks in weblab-macros
+            Item::Verbatim(ts) => {
+                if let Ok(File { items, .. }) = syn::parse2(ts.clone()) {
+                    collect_public_api(&items, prefix, res);
+                }
+            }
+            _ => {}
+        }
+    }
+}
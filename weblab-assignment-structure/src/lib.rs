@@ -1,3 +1,7 @@
+#[cfg(feature = "api")]
+pub mod api;
+pub mod markdown;
+
 pub enum WeblabAttribute {
     Test,
     Library,
@@ -7,23 +11,62 @@ pub enum WeblabAttribute {
 pub struct ProgrammingAssignment {
     pub title: &'static str,
     pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
 
     pub library_visible: bool,
     pub spectest_stdout_visible: bool,
+    pub clippy: bool,
+    pub require_fmt: bool,
+    pub forbidden: &'static [ForbiddenConstruct],
+    pub api: &'static [ApiItem],
     pub test: &'static str,
     pub solution: &'static str,
-    pub library: Option<&'static str>,
+    pub library: &'static [SourceFile],
     pub test_template: &'static str,
     pub solution_template: &'static str,
     pub checklist: Option<Checklist>,
 }
 
+#[derive(Copy, Clone)]
+pub enum ForbiddenConstruct {
+    Keyword(&'static str),
+    Path(&'static str),
+    Macro(&'static str),
+}
+
+/// A source file of an assignment, like one of its library modules
+pub struct SourceFile {
+    /// the name of the module in the file (without `.rs`)
+    pub name: &'static str,
+    pub contents: &'static str,
+}
+
+/// A file (like an image) that's exported together with an assignment
+pub struct Asset {
+    /// where the file is, relative to the crate root
+    pub path: &'static str,
+    pub contents: &'static [u8],
+}
+
+pub struct ApiItem {
+    pub path: &'static str,
+    pub signature: &'static str,
+}
+
 pub struct WeblabFolder {
     pub title: &'static str,
     pub assignment_text: &'static str,
     pub assignments: &'static [WeblabAssignment],
 }
 
+/// A folder of which every student gets `pick` randomly chosen assignments
+pub struct QuestionPool {
+    pub title: &'static str,
+    pub assignment_text: &'static str,
+    pub pick: usize,
+    pub assignments: &'static [WeblabAssignment],
+}
+
 pub struct Checklist {
     pub items: Vec<&'static str>,
 }
@@ -32,6 +75,7 @@ pub struct OpenQuestion {
     pub title: &'static str,
 
     pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
 
     pub expected_answer: &'static str,
     pub checklist: Option<Checklist>,
@@ -48,6 +92,8 @@ pub struct InlineQuestionList {
 pub struct MCOption {
     pub text: &'static str,
     pub is_correct: bool,
+    /// Shown to students who picked this option. Empty when there's no feedback.
+    pub feedback: &'static str,
 }
 
 #[derive(Copy, Clone)]
@@ -65,6 +111,7 @@ impl Default for MCStyle {
 pub struct MCQuestion {
     pub title: &'static str,
     pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
 
     pub options: &'static [MCOption],
     pub randomize: bool,
@@ -72,11 +119,108 @@ pub struct MCQuestion {
     pub explanation: &'static str,
 }
 
+pub struct NumericQuestion {
+    pub title: &'static str,
+    pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
+
+    pub answer: f64,
+    pub tolerance: f64,
+    pub explanation: &'static str,
+}
+
+pub struct TrueFalseQuestion {
+    pub title: &'static str,
+    pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
+
+    pub answer: bool,
+    pub explanation: &'static str,
+}
+
+pub struct ClozeBlank {
+    /// every answer accepted for this blank
+    pub answers: &'static [&'static str],
+}
+
+pub struct ClozeQuestion {
+    pub title: &'static str,
+    /// The question text, in which the blanks are numbered like `[[1]]`
+    pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
+
+    pub blanks: &'static [ClozeBlank],
+    pub explanation: &'static str,
+}
+
+pub struct PredictOutputQuestion {
+    pub title: &'static str,
+    pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
+
+    /// The program of which the output should be predicted. The expected answer
+    /// is found by running it when the assignment is generated.
+    pub code: &'static str,
+}
+
+#[derive(Copy, Clone)]
+pub enum CompileResult {
+    Compiles,
+    /// compiling fails, with any error
+    Fails,
+    /// compiling fails with this error code, like `E0382`
+    Error(&'static str),
+}
+
+pub struct CompilesQuestion {
+    pub title: &'static str,
+    pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
+
+    /// The snippet of which students decide whether it compiles. The cli checks
+    /// it gives the `expected` result when the assignment is generated.
+    pub code: &'static str,
+    pub expected: CompileResult,
+    pub explanation: &'static str,
+}
+
+pub struct MatchingPair {
+    pub left: &'static str,
+    pub right: &'static str,
+}
+
+pub struct MatchingQuestion {
+    pub title: &'static str,
+    pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
+
+    pub pairs: &'static [MatchingPair],
+    pub explanation: &'static str,
+}
+
+pub struct OrderingQuestion {
+    pub title: &'static str,
+    pub assignment_text: &'static str,
+    pub assets: &'static [Asset],
+
+    /// The steps in the correct order
+    pub steps: &'static [&'static str],
+    pub explanation: &'static str,
+}
+
 pub enum WeblabAssignment {
     Programming(ProgrammingAssignment),
     Open(OpenQuestion),
     MultipleChoice(MCQuestion),
+    Numeric(NumericQuestion),
+    TrueFalse(TrueFalseQuestion),
+    Cloze(ClozeQuestion),
+    PredictOutput(PredictOutputQuestion),
+    Compiles(CompilesQuestion),
+    Matching(MatchingQuestion),
+    Ordering(OrderingQuestion),
     Folder(WeblabFolder),
+    Pool(QuestionPool),
     InlineQuestionList(InlineQuestionList),
 }
 
@@ -86,10 +230,35 @@ impl WeblabAssignment {
             WeblabAssignment::Programming(ProgrammingAssignment { title, .. }) => title,
             WeblabAssignment::Open(OpenQuestion { title, .. }) => title,
             WeblabAssignment::MultipleChoice(MCQuestion { title, .. }) => title,
+            WeblabAssignment::Numeric(NumericQuestion { title, .. }) => title,
+            WeblabAssignment::TrueFalse(TrueFalseQuestion { title, .. }) => title,
+            WeblabAssignment::Cloze(ClozeQuestion { title, .. }) => title,
+            WeblabAssignment::PredictOutput(PredictOutputQuestion { title, .. }) => title,
+            WeblabAssignment::Compiles(CompilesQuestion { title, .. }) => title,
+            WeblabAssignment::Matching(MatchingQuestion { title, .. }) => title,
+            WeblabAssignment::Ordering(OrderingQuestion { title, .. }) => title,
             WeblabAssignment::Folder(WeblabFolder { title, .. }) => title,
+            WeblabAssignment::Pool(QuestionPool { title, .. }) => title,
             WeblabAssignment::InlineQuestionList(InlineQuestionList { title, .. }) => title,
         }
     }
+    pub fn assets(&self) -> &[Asset] {
+        match self {
+            WeblabAssignment::Programming(ProgrammingAssignment { assets, .. }) => assets,
+            WeblabAssignment::Open(OpenQuestion { assets, .. }) => assets,
+            WeblabAssignment::MultipleChoice(MCQuestion { assets, .. }) => assets,
+            WeblabAssignment::Numeric(NumericQuestion { assets, .. }) => assets,
+            WeblabAssignment::TrueFalse(TrueFalseQuestion { assets, .. }) => assets,
+            WeblabAssignment::Cloze(ClozeQuestion { assets, .. }) => assets,
+            WeblabAssignment::PredictOutput(PredictOutputQuestion { assets, .. }) => assets,
+            WeblabAssignment::Compiles(CompilesQuestion { assets, .. }) => assets,
+            WeblabAssignment::Matching(MatchingQuestion { assets, .. }) => assets,
+            WeblabAssignment::Ordering(OrderingQuestion { assets, .. }) => assets,
+            WeblabAssignment::Folder(_)
+            | WeblabAssignment::Pool(_)
+            | WeblabAssignment::InlineQuestionList(_) => &[],
+        }
+    }
     pub fn assignment_text(&self) -> &str {
         match self {
             WeblabAssignment::Programming(ProgrammingAssignment {
@@ -101,9 +270,33 @@ impl WeblabAssignment {
             WeblabAssignment::MultipleChoice(MCQuestion {
                 assignment_text, ..
             }) => assignment_text,
+            WeblabAssignment::Numeric(NumericQuestion {
+                assignment_text, ..
+            }) => assignment_text,
+            WeblabAssignment::TrueFalse(TrueFalseQuestion {
+                assignment_text, ..
+            }) => assignment_text,
+            WeblabAssignment::Cloze(ClozeQuestion {
+                assignment_text, ..
+            }) => assignment_text,
+            WeblabAssignment::PredictOutput(PredictOutputQuestion {
+                assignment_text, ..
+            }) => assignment_text,
+            WeblabAssignment::Compiles(CompilesQuestion {
+                assignment_text, ..
+            }) => assignment_text,
+            WeblabAssignment::Matching(MatchingQuestion {
+                assignment_text, ..
+            }) => assignment_text,
+            WeblabAssignment::Ordering(OrderingQuestion {
+                assignment_text, ..
+            }) => assignment_text,
             WeblabAssignment::Folder(WeblabFolder {
                 assignment_text, ..
             }) => assignment_text,
+            WeblabAssignment::Pool(QuestionPool {
+                assignment_text, ..
+            }) => assignment_text,
             WeblabAssignment::InlineQuestionList(InlineQuestionList {
                 assignment_text, ..
             }) => assignment_text,
@@ -0,0 +1,207 @@
+//! Finding the images in the markdown text of assignments. weblab-macros uses this to
+//! resolve images relative to the markdown file they're in, and the cli to point
+//! them to where the assets are copied.
+
+use std::ops::Range;
+
+/// The byte ranges in `text` of the paths of images (`![alt](path "title")`).
+/// Images in code blocks and code spans aren't images.
+pub fn image_paths(text: &str) -> Vec<Range<usize>> {
+    let mut res = Vec::new();
+    let mut fence = None;
+    let mut line_start = 0;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start_matches(' ');
+        let line_fence = ["```", "~~~"].into_iter().find(|i| trimmed.starts_with(i));
+        match (fence, line_fence) {
+            (Some(open), Some(close)) if open == close => fence = None,
+            (Some(_), _) => {}
+            (None, Some(open)) => fence = Some(open),
+            (None, None) => images_in_line(line, line_start, &mut res),
+        }
+
+        line_start += line.len();
+    }
+
+    res
+}
+
+/// Whether an image path refers to a file of the assignment, and not to a url
+/// or an absolute path
+pub fn is_relative(path: &str) -> bool {
+    !(path.is_empty() || path.contains(':') || path.starts_with('/') || path.starts_with('#'))
+}
+
+/// `path` without `.` and `dir/..`, using `/` as separator. `..` that go above
+/// where `path` starts are kept.
+pub fn normalize_path(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|i| *i != "..") => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+
+    parts.join("/")
+}
+
+fn images_in_line(line: &str, offset: usize, res: &mut Vec<Range<usize>>) {
+    let mut i = 0;
+    while let Some(c) = line[i..].chars().next() {
+        let rest = &line[i..];
+
+        if c == '\\' {
+            // an escaped character, like `\!`
+            i += 1 + rest[1..].chars().next().map_or(0, char::len_utf8);
+        } else if c == '`' {
+            // a code span ends at the next run of exactly as many backticks
+            let ticks = backticks(rest);
+            i += ticks + closing_backticks(&rest[ticks..], ticks).map_or(0, |end| end + ticks);
+        } else if let Some((path, end)) = rest.strip_prefix("![").and_then(image) {
+            res.push(offset + i + 2 + path.start..offset + i + 2 + path.end);
+            i += 2 + end;
+        } else {
+            i += c.len_utf8();
+        }
+    }
+}
+
+fn backticks(text: &str) -> usize {
+    text.len() - text.trim_start_matches('`').len()
+}
+
+fn closing_backticks(text: &str, count: usize) -> Option<usize> {
+    let mut start = 0;
+    while let Some(found) = text[start..].find('`') {
+        let found = start + found;
+        let run = backticks(&text[found..]);
+        if run == count {
+            return Some(found);
+        }
+        start = found + run;
+    }
+    None
+}
+
+/// Parses the rest of an image after `![`, and returns the range of its
+/// path and where the image ends
+fn image(text: &str) -> Option<(Range<usize>, usize)> {
+    let mut depth = 0;
+    let alt_end = text.char_indices().find_map(|(i, c)| {
+        match c {
+            '[' => depth += 1,
+            ']' if depth == 0 => return Some(i),
+            ']' => depth -= 1,
+            _ => {}
+        }
+        None
+    })?;
+
+    let destination_start = alt_end + 2;
+    let destination = text[alt_end + 1..].strip_prefix('(')?;
+    let path_start = destination_start + destination.len() - destination.trim_start().len();
+    let destination = &text[path_start..];
+
+    let (path, path_end) = if let Some(inner) = destination.strip_prefix('<') {
+        // `![alt](<path with spaces>)`
+        let end = inner.find('>')?;
+        (path_start + 1..path_start + 1 + end, path_start + end + 2)
+    } else {
+        let mut depth = 0;
+        let end = destination
+            .char_indices()
+            .find_map(|(i, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' if depth == 0 => return Some(i),
+                    ')' => depth -= 1,
+                    c if c.is_whitespace() => return Some(i),
+                    _ => {}
+                }
+                None
+            })
+            .unwrap_or(destination.len());
+        (path_start..path_start + end, path_start + end)
+    };
+
+    // an optional title, like `"title"`, `'title'` or `(title)`
+    let rest = &text[path_end..];
+    let title_start = path_end + rest.len() - rest.trim_start().len();
+    let title_end = match text[title_start..].chars().next()? {
+        ')' => title_start,
+        open @ ('"' | '\'' | '(') => {
+            let close = if open == '(' { ')' } else { open };
+            title_start + 1 + text[title_start + 1..].find(close)? + 1
+        }
+        _ => return None,
+    };
+
+    let rest = &text[title_end..];
+    let close = title_end + rest.len() - rest.trim_start().len();
+    text[close..].starts_with(')').then_some((path, close + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(text: &str) -> Vec<&str> {
+        image_paths(text).into_iter().map(|i| &text[i]).collect()
+    }
+
+    #[test]
+    fn images() {
+        assert_eq!(paths("a ![tree](img/tree.png) b"), ["img/tree.png"]);
+        assert_eq!(
+            paths("![a [nested] alt](a.png)![](b.png)"),
+            ["a.png", "b.png"]
+        );
+        assert_eq!(paths("![x](<with space.png>)"), ["with space.png"]);
+        assert_eq!(paths("![x](a_(1).png)"), ["a_(1).png"]);
+        assert_eq!(
+            paths("[not an image](a.png) \\![escaped](b.png)"),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn titles() {
+        assert_eq!(
+            paths("![a](a.png \"title\") ![b](b.png 'title') ![c]( c.png (title) )"),
+            ["a.png", "b.png", "c.png"]
+        );
+        assert_eq!(paths("![a](a.png title)"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn code() {
+        let text = "`![a](a.png)` ``x ` ![b](b.png)`` ![c](c.png)\n\
+            ```rust\n\
+            let s = \"![d](d.png)\";\n\
+            ```\n\
+            f(a[0](1)) ![e](e.png)";
+        assert_eq!(paths(text), ["c.png", "e.png"]);
+    }
+
+    #[test]
+    fn relative() {
+        assert!(is_relative("img/tree.png"));
+        assert!(is_relative("../tree.png"));
+        assert!(!is_relative("https://example.com/tree.png"));
+        assert!(!is_relative("/tree.png"));
+        assert!(!is_relative("#tree"));
+        assert!(!is_relative(""));
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_path("./img/../img/tree.png"), "img/tree.png");
+        assert_eq!(normalize_path("questions/../../tree.png"), "../tree.png");
+        assert_eq!(normalize_path("a\\b.png"), "a/b.png");
+    }
+}
@@ -0,0 +1,37 @@
+#!/usr/bin/env bash
+set -o nounset
+set -o pipefail
+set -o errexit
+
+# Copies the files from WebLab into src/ and declares them in main.rs.
+# library.txt either is the library module itself, or contains multiple
+# library modules, each starting with a `// weblab-file: name.rs` line.
+
+marker='^// weblab-file: [A-Za-z_][A-Za-z0-9_]*\.rs$'
+
+cp solution.txt src/solution.rs 2>/dev/null || true
+cp test.txt     src/test.rs     2>/dev/null || true
+
+modules="solution test"
+if [ -f library.txt ] && grep -q "$marker" library.txt; then
+    rm -f src/library.rs
+    # compares the prefix as a string, the name the same way as $marker
+    awk '
+        index($0, "// weblab-file: ") == 1 && substr($0, 17) ~ /^[A-Za-z_][A-Za-z0-9_]*\.rs$/ {
+            file = "src/" substr($0, 17); printf "" > file; next
+        }
+        file { print > file }
+    ' library.txt
+    modules="$(sed -n 's|^// weblab-file: \([A-Za-z_][A-Za-z0-9_]*\)\.rs$|\1|p' library.txt) $modules"
+else
+    cp library.txt src/library.rs 2>/dev/null || true
+    modules="library $modules"
+fi
+
+{
+    for m in $modules; do
+        echo "mod $m;"
+    done
+    echo
+    echo "fn main() {}"
+} > src/main.rs
@@ -0,0 +1,26 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::Item;
+use weblab_assignment_structure::api::PublicItem;
+
+/// A public item of the solution template, recorded in the assignment as `ApiItem`
+pub struct ApiItem(PublicItem);
+
+impl ToTokens for ApiItem {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let PublicItem { path, signature } = &self.0;
+        tokens.extend(quote! {
+            ApiItem {
+                path: #path,
+                signature: #signature,
+            }
+        })
+    }
+}
+
+pub fn public_api(items: &[Item]) -> Vec<ApiItem> {
+    weblab_assignment_structure::api::public_api(items)
+        .into_iter()
+        .map(ApiItem)
+        .collect()
+}
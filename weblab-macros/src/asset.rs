@@ -0,0 +1,51 @@
+use crate::open::manifest_path;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::LitStr;
+
+/// A file exported together with an assignment, like an image used in its text
+/// (`#[weblab(asset = "img/tree.png")]`, or `asset: "img/tree.png"` in questions)
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssetFile {
+    /// the path as written, relative to the crate root
+    pub path: String,
+    pub full_path: String,
+}
+
+impl AssetFile {
+    pub fn from_lit(path: &LitStr) -> syn::Result<Self> {
+        let full_path = manifest_path(path)?;
+        if !full_path.is_file() {
+            return Err(syn::Error::new(
+                path.span(),
+                format!("asset {} doesn't exist", full_path.display()),
+            ));
+        }
+
+        Ok(Self {
+            path: path.value(),
+            full_path: full_path.to_string_lossy().into_owned(),
+        })
+    }
+}
+
+impl Parse for AssetFile {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Self::from_lit(&input.parse()?)
+    }
+}
+
+impl ToTokens for AssetFile {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self { path, full_path } = self;
+
+        // `include_bytes!` also makes cargo rebuild when the asset changes
+        tokens.extend(quote! {
+            Asset {
+                path: #path,
+                contents: include_bytes!(#full_path),
+            }
+        });
+    }
+}
@@ -1,23 +1,52 @@
+use crate::asset::AssetFile;
+use crate::forbid::Forbidden;
+use crate::open::read_markdown_file;
+use crate::performance::DEFAULT_MAX_SLOWDOWN;
 use crate::Span2;
-use proc_macro::{Span, TokenStream};
+use proc_macro::TokenStream;
 use quote::ToTokens;
-use syn::parse::{Parse, ParseStream};
-use syn::{Attribute, AttributeArgs, Lit, Meta, MetaNameValue, NestedMeta, Token};
+use syn::parse::{ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, Lit, Meta, MetaList, MetaNameValue, NestedMeta, Path, Token};
 
-pub enum ToAttrError {
-    Message(String),
-    Spanned(Span2, String),
-}
+/// Attributes used as `#[weblab(name)]`
+const FLAGS: &[&str] = &[
+    "programming_assignment",
+    "solution",
+    "solution_template",
+    "test",
+    "test_template",
+    "library",
+    "performance",
+    "require_fmt",
+    "stub",
+];
+/// Attributes used as `#[weblab(name = ...)]`
+const VALUES: &[&str] = &[
+    "title",
+    "description",
+    "description_file",
+    "asset",
+    "file",
+    "lint",
+    "performance",
+    "stub",
+];
+/// Attributes used as `#[weblab(name(...))]`
+const LISTS: &[&str] = &["forbid"];
 
-impl ToAttrError {
-    fn into_token_stream(self) -> TokenStream {
-        match self {
-            ToAttrError::Spanned(s, m) => syn::Error::new(s, m).to_compile_error().into(),
-            ToAttrError::Message(s) => syn::Error::new(Span::call_site().into(), s)
-                .to_compile_error()
-                .into(),
-        }
-    }
+/// Where a `#[weblab(...)]` attribute is used
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Target {
+    /// the module with `#[weblab(programming_assignment)]`
+    Assignment,
+    /// a module inside the assignment
+    Module,
+    /// a function or method inside the assignment
+    Function,
+    /// any other item inside the assignment
+    Other,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -31,30 +60,43 @@ pub enum Attr {
 
     Title(String),
     Doc(String),
+    /// the assignment text, read from a file
+    DocFile {
+        path: String,
+        text: String,
+    },
+    Asset(AssetFile),
+    /// the contents of the module are in this file (relative to the crate root)
+    File(String),
+
+    Performance(f64),
+    Clippy,
+    RequireFmt,
+    Forbid(Vec<Forbidden>),
+    /// replace the function body by `todo!()`, or the given expression, in the template
+    Stub(Option<String>),
 }
 
 impl TryFrom<NestedMeta> for Attr {
-    type Error = ToAttrError;
+    type Error = syn::Error;
 
     fn try_from(value: NestedMeta) -> Result<Self, Self::Error> {
         match value {
-            NestedMeta::Meta(m) => return m.try_into(),
-            NestedMeta::Lit(_) => {}
+            NestedMeta::Meta(m) => m.try_into(),
+            NestedMeta::Lit(l) => Err(syn::Error::new(
+                l.span(),
+                format!("expected an attribute name, one of {}", expected_names()),
+            )),
         }
-
-        Err(ToAttrError::Message(
-            "expected one of 'programming_assignment', 'solution', 'solution_template', 'test', \
-            'test_template', 'library', 'title=\"...\"' or 'description=\"...\"'"
-                .to_string(),
-        ))
     }
 }
+
 impl TryFrom<Meta> for Attr {
-    type Error = ToAttrError;
+    type Error = syn::Error;
 
     fn try_from(value: Meta) -> Result<Self, Self::Error> {
         match value {
-            Meta::Path(path) => {
+            Meta::Path(ref path) => {
                 if let Some(i) = path.get_ident() {
                     match i.to_string().as_str() {
                         "programming_assignment" => return Ok(Self::ProgrammingAssignment),
@@ -63,15 +105,27 @@ impl TryFrom<Meta> for Attr {
                         "test" => return Ok(Self::Test),
                         "test_template" => return Ok(Self::TestTemplate),
                         "library" => return Ok(Self::Library),
+                        "performance" => return Ok(Self::Performance(DEFAULT_MAX_SLOWDOWN)),
+                        "require_fmt" => return Ok(Self::RequireFmt),
+                        "stub" => return Ok(Self::Stub(None)),
                         _ => {}
                     }
                 }
             }
-            Meta::List(_) => {}
+            Meta::List(MetaList {
+                ref path,
+                ref nested,
+                ..
+            }) => {
+                if path.is_ident("forbid") {
+                    return collect_errors(nested.iter().cloned().map(Forbidden::try_from))
+                        .map(Self::Forbid);
+                }
+            }
             Meta::NameValue(MetaNameValue {
-                path,
+                ref path,
                 eq_token: _,
-                lit,
+                ref lit,
             }) => {
                 if let Some(i) = path.get_ident() {
                     match i.to_string().as_str() {
@@ -79,7 +133,7 @@ impl TryFrom<Meta> for Attr {
                             if let Lit::Str(s) = lit {
                                 return Ok(Self::Title(s.value()));
                             } else {
-                                return Err(ToAttrError::Spanned(
+                                return Err(syn::Error::new(
                                     lit.span(),
                                     "expected string".to_string(),
                                 ));
@@ -89,89 +143,292 @@ impl TryFrom<Meta> for Attr {
                             if let Lit::Str(s) = lit {
                                 return Ok(Self::Doc(s.value()));
                             } else {
-                                return Err(ToAttrError::Spanned(
+                                return Err(syn::Error::new(
                                     lit.span(),
                                     "expected string".to_string(),
                                 ));
                             }
                         }
-                        _ => {
-                            return Err(ToAttrError::Spanned(
-                                i.span(),
-                                "expected 'title' or 'description'".to_string(),
-                            ))
+                        "description_file" => {
+                            return match lit {
+                                Lit::Str(s) => read_markdown_file(s)
+                                    .map(|(path, text)| Self::DocFile { path, text }),
+                                _ => {
+                                    Err(syn::Error::new(lit.span(), "expected string".to_string()))
+                                }
+                            };
+                        }
+                        "file" => {
+                            return match lit {
+                                Lit::Str(s) => Ok(Self::File(s.value())),
+                                _ => {
+                                    Err(syn::Error::new(lit.span(), "expected string".to_string()))
+                                }
+                            };
+                        }
+                        "asset" => {
+                            return match lit {
+                                Lit::Str(s) => AssetFile::from_lit(s).map(Self::Asset),
+                                _ => {
+                                    Err(syn::Error::new(lit.span(), "expected string".to_string()))
+                                }
+                            };
+                        }
+                        "lint" => {
+                            return match lit {
+                                Lit::Str(s) if s.value() == "clippy" => Ok(Self::Clippy),
+                                Lit::Str(s) => Err(syn::Error::new(
+                                    s.span(),
+                                    "unknown linter, expected \"clippy\"".to_string(),
+                                )),
+                                _ => {
+                                    Err(syn::Error::new(lit.span(), "expected string".to_string()))
+                                }
+                            };
+                        }
+                        "performance" => {
+                            let max_slowdown = match &lit {
+                                Lit::Float(f) => f.base10_parse::<f64>().ok(),
+                                Lit::Int(i) => i.base10_parse::<f64>().ok(),
+                                _ => None,
+                            };
+
+                            return match max_slowdown {
+                                Some(i) if i >= 1.0 => Ok(Self::Performance(i)),
+                                _ => Err(syn::Error::new(
+                                    lit.span(),
+                                    "expected a number of at least 1 (the maximum factor the \
+                                    solution may be slower than the reference solution)"
+                                        .to_string(),
+                                )),
+                            };
+                        }
+                        "stub" => {
+                            return match lit {
+                                Lit::Str(s) => match syn::parse_str::<Expr>(&s.value()) {
+                                    Ok(_) => Ok(Self::Stub(Some(s.value()))),
+                                    Err(e) => Err(syn::Error::new(
+                                        s.span(),
+                                        format!("expected an expression: {e}"),
+                                    )),
+                                },
+                                _ => {
+                                    Err(syn::Error::new(lit.span(), "expected string".to_string()))
+                                }
+                            };
                         }
+                        _ => {}
                     }
                 }
             }
         }
 
-        Err(ToAttrError::Message(
-            "expected one of 'programming_assignment', 'solution', 'solution_template', 'test', \
-            'test_template', 'library', 'main', 'title=\"...\"' or 'description=\"...\"'"
-                .to_string(),
-        ))
+        Err(unexpected(value.path()))
     }
 }
 
-pub enum ParseAttrStatus {
-    NotParsed(Attribute),
-    Doc(Attr, Attribute),
-    Attr(Vec<Attr>),
+impl Attr {
+    /// The name used in `#[weblab(...)]` for this attribute
+    fn name(&self) -> &'static str {
+        match self {
+            Attr::ProgrammingAssignment => "programming_assignment",
+            Attr::Solution => "solution",
+            Attr::SolutionTemplate => "solution_template",
+            Attr::Test => "test",
+            Attr::TestTemplate => "test_template",
+            Attr::Library => "library",
+            Attr::Title(_) => "title",
+            Attr::Doc(_) => "description",
+            Attr::DocFile { .. } => "description_file",
+            Attr::Asset(_) => "asset",
+            Attr::File(_) => "file",
+            Attr::Performance(_) => "performance",
+            Attr::Clippy => "lint",
+            Attr::RequireFmt => "require_fmt",
+            Attr::Forbid(_) => "forbid",
+            Attr::Stub(_) => "stub",
+        }
+    }
+
+    /// Why this attribute can't be used on `target`, if it can't
+    pub fn misplaced(&self, target: Target) -> Option<String> {
+        let name = self.name();
+        match self {
+            Attr::ProgrammingAssignment if target != Target::Assignment => Some(
+                "#[weblab(programming_assignment)] can't be used inside another assignment"
+                    .to_string(),
+            ),
+            Attr::Title(_)
+            | Attr::DocFile { .. }
+            | Attr::Asset(_)
+            | Attr::Clippy
+            | Attr::RequireFmt
+            | Attr::Forbid(_)
+                if target != Target::Assignment =>
+            {
+                Some(format!(
+                    "#[weblab({name})] can only be used on the module with \
+                    #[weblab(programming_assignment)]"
+                ))
+            }
+            Attr::Solution
+            | Attr::SolutionTemplate
+            | Attr::Test
+            | Attr::TestTemplate
+            | Attr::Library
+            | Attr::File(_)
+                if target != Target::Module =>
+            {
+                Some(format!(
+                    "#[weblab({name})] can only be used on a module inside the assignment"
+                ))
+            }
+            Attr::Performance(_) if target != Target::Function => {
+                Some("#[weblab(performance)] can only be used on test functions".to_string())
+            }
+            Attr::Stub(_) if target != Target::Function => {
+                Some("#[weblab(stub)] can only be used on functions and methods".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// All valid attribute names, for error messages
+fn expected_names() -> String {
+    let mut names = FLAGS
+        .iter()
+        .map(|i| format!("`{i}`"))
+        .chain(VALUES.iter().map(|i| format!("`{i} = ...`")))
+        .chain(LISTS.iter().map(|i| format!("`{i}(...)`")))
+        .collect::<Vec<_>>();
+    let last = names.pop().unwrap_or_default();
+    format!("{} or {last}", names.join(", "))
 }
 
-pub fn parse_attr(attr: Attribute) -> Result<ParseAttrStatus, TokenStream> {
-    struct Args {
-        metas: Vec<NestedMeta>,
+/// The error for an attribute that's unknown, or used in the wrong way
+/// (like `solution = "..."`), with a suggestion when it looks like a typo.
+fn unexpected(path: &Path) -> syn::Error {
+    let name = path.to_token_stream().to_string().replace(' ', "");
+
+    let mut usages = Vec::new();
+    if FLAGS.contains(&name.as_str()) {
+        usages.push(format!("`#[weblab({name})]`"));
+    }
+    if VALUES.contains(&name.as_str()) {
+        usages.push(format!("`#[weblab({name} = ...)]`"));
+    }
+    if LISTS.contains(&name.as_str()) {
+        usages.push(format!("`#[weblab({name}(...))]`"));
     }
 
-    impl Parse for Args {
-        fn parse(input: ParseStream) -> syn::Result<Self> {
-            let parsed =
-                syn::punctuated::Punctuated::<NestedMeta, Token![,]>::parse_separated_nonempty(
-                    input,
-                )?;
-            Ok(Self {
-                metas: parsed.into_iter().collect(),
-            })
+    let message = if !usages.is_empty() {
+        format!("`{name}` is used as {}", usages.join(" or "))
+    } else if let Some(suggestion) = closest(&name, FLAGS.iter().chain(VALUES).chain(LISTS)) {
+        format!("unknown weblab attribute `{name}`, did you mean `{suggestion}`?")
+    } else {
+        format!(
+            "unknown weblab attribute `{name}`, expected one of {}",
+            expected_names()
+        )
+    };
+
+    syn::Error::new(path.span(), message)
+}
+
+/// The name in `names` that's closest to `name`, if it's close enough to be a typo
+pub fn closest<'a>(name: &str, names: impl IntoIterator<Item = &'a &'a str>) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    names
+        .into_iter()
+        .map(|i| (edit_distance(name, i), *i))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, i)| i)
+}
+
+/// The number of characters to insert, remove, change or swap to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // distances[i][j] is the distance between a[..i] and b[..j]
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let change = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + change);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
         }
     }
 
-    match attr.path.get_ident() {
-        Some(i) if *i == "doc" => {
-            let meta = attr
-                .parse_meta()
-                .map_err(|e| e.to_compile_error().to_token_stream())?;
+    distances[a.len()][b.len()]
+}
 
-            let parsed_attr: Attr = meta
-                .try_into()
-                .map_err(|e: ToAttrError| e.into_token_stream())?;
+/// Collects all values, or all errors combined into one so they're all reported at once
+pub fn collect_errors<T>(results: impl IntoIterator<Item = syn::Result<T>>) -> syn::Result<Vec<T>> {
+    let mut values = Vec::new();
+    let mut error: Option<syn::Error> = None;
 
-            Ok(ParseAttrStatus::Doc(parsed_attr, attr.clone()))
+    for i in results {
+        match i {
+            Ok(i) => values.push(i),
+            Err(e) => match &mut error {
+                Some(error) => error.combine(e),
+                None => error = Some(e),
+            },
         }
-        Some(i) if *i == "weblab" => {
-            let args = attr
-                .parse_args::<Args>()
-                .map_err(|e| e.to_compile_error().to_token_stream())?;
-
-            let attrs = args
-                .metas
-                .into_iter()
-                .map(Attr::try_from)
-                .collect::<Result<_, _>>()
-                .map_err(|e| e.into_token_stream())?;
-
-            Ok(ParseAttrStatus::Attr(attrs))
+    }
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(values),
+    }
+}
+
+pub enum ParseAttrStatus {
+    NotParsed(Attribute),
+    Doc(Attr, Attribute),
+    /// the parsed attributes, with where they are to report errors at
+    Attr(Vec<(Attr, Span2)>),
+}
+
+/// Parses the contents of `#[weblab(...)]`, reporting all errors in it at once
+fn parse_args(input: ParseStream) -> syn::Result<Vec<(Attr, Span2)>> {
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let metas = Punctuated::<NestedMeta, Token![,]>::parse_separated_nonempty(input)?;
+    collect_errors(metas.into_iter().map(|i| {
+        let span = i.span();
+        Attr::try_from(i).map(|attr| (attr, span))
+    }))
+}
+
+pub fn parse_attr(attr: Attribute) -> syn::Result<ParseAttrStatus> {
+    match attr.path.get_ident() {
+        Some(i) if *i == "doc" => {
+            let parsed_attr = Attr::try_from(attr.parse_meta()?)?;
+            Ok(ParseAttrStatus::Doc(parsed_attr, attr))
         }
+        Some(i) if *i == "weblab" => Ok(ParseAttrStatus::Attr(attr.parse_args_with(parse_args)?)),
         _ => Ok(ParseAttrStatus::NotParsed(attr)),
     }
 }
 
-pub fn parse_attr_stream(attr: TokenStream) -> Result<Vec<Attr>, TokenStream> {
-    syn::parse_macro_input::parse::<AttributeArgs>(attr)
-        .map_err(|e| -> TokenStream { e.to_compile_error().into() })?
-        .into_iter()
-        .map(Attr::try_from)
-        .collect::<Result<_, _>>()
-        .map_err(|e| e.into_token_stream())
+pub fn parse_attr_stream(attr: TokenStream) -> syn::Result<Vec<(Attr, Span2)>> {
+    parse_args.parse(attr)
 }
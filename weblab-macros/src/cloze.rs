@@ -0,0 +1,96 @@
+use crate::asset::AssetFile;
+use crate::open::DocString;
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Token};
+
+/// The question text of a cloze question, in which every blank
+/// (`[[answer]]` or `[[a|b]]` with multiple accepted answers) is
+/// replaced by its number (`[[1]]`, `[[2]]`, ...).
+pub struct ClozeText {
+    pub text: String,
+    /// the accepted answers for every blank
+    pub blanks: Vec<Vec<String>>,
+}
+
+impl ClozeText {
+    pub fn from_doc(doc: &DocString) -> Result<Self, String> {
+        let mut text = String::new();
+        let mut blanks = Vec::new();
+        let mut rest = doc.text.as_str();
+
+        while let Some(start) = rest.find("[[") {
+            text.push_str(&rest[..start]);
+            rest = &rest[start + 2..];
+
+            let end = rest
+                .find("]]")
+                .ok_or_else(|| "blank starting with `[[` is never closed with `]]`".to_string())?;
+            let answers = rest[..end]
+                .split('|')
+                .map(|i| i.trim().to_string())
+                .collect::<Vec<_>>();
+            if answers.iter().any(|i| i.is_empty()) {
+                return Err(format!(
+                    "blank `[[{}]]` contains an empty answer",
+                    &rest[..end]
+                ));
+            }
+
+            blanks.push(answers);
+            text.push_str(&format!("[[{}]]", blanks.len()));
+            rest = &rest[end + 2..];
+        }
+        text.push_str(rest);
+
+        Ok(Self { text, blanks })
+    }
+}
+
+pub struct ClozeQuestion {
+    pub title: String,
+    pub question_text: DocString,
+    pub assets: Vec<AssetFile>,
+    /// where the question text starts, to report errors in blanks at
+    pub question_span: Span,
+    pub explanation: DocString,
+}
+
+impl Parse for ClozeQuestion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut res = Self {
+            title: String::new(),
+            question_text: Default::default(),
+            assets: Vec::new(),
+            question_span: Span::call_site(),
+            explanation: Default::default(),
+        };
+
+        while !input.is_empty() {
+            let field: Ident = input.parse()?;
+            let _colon: Token!(:) = input.parse()?;
+
+            match field.to_string().as_str() {
+                "title" => res.title = input.parse::<LitStr>()?.value(),
+                "question" => {
+                    res.question_span = input.span();
+                    res.question_text = input.parse()?
+                }
+                "explanation" => res.explanation = input.parse()?,
+                "asset" => res.assets.push(input.parse()?),
+                n => {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        format!("unexpected field name {}", n),
+                    ))
+                }
+            }
+
+            if input.peek(Token!(,)) {
+                let _comma = input.parse::<Token!(,)>()?;
+            }
+        }
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,104 @@
+use crate::asset::AssetFile;
+use crate::open::DocString;
+use crate::predict_output::parse_code;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Token};
+
+/// What the author claims rustc does with the snippet
+pub enum CompileResult {
+    Compiles,
+    /// fails with any error
+    Fails,
+    /// fails with this error code, like `E0382`
+    Error(String),
+}
+
+impl ToTokens for CompileResult {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            CompileResult::Compiles => quote! {CompileResult::Compiles},
+            CompileResult::Fails => quote! {CompileResult::Fails},
+            CompileResult::Error(code) => quote! {CompileResult::Error(#code)},
+        })
+    }
+}
+
+impl Parse for CompileResult {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kind: Ident = input.parse()?;
+        match kind.to_string().as_str() {
+            "compiles" => Ok(CompileResult::Compiles),
+            "error" if !input.peek(Ident) => Ok(CompileResult::Fails),
+            "error" => {
+                let code: Ident = input.parse()?;
+                let name = code.to_string();
+                let is_error_code = name.len() == 5
+                    && name.starts_with('E')
+                    && name[1..].chars().all(|c| c.is_ascii_digit());
+
+                if is_error_code {
+                    Ok(CompileResult::Error(name))
+                } else {
+                    Err(syn::Error::new(
+                        code.span(),
+                        "expected an error code like `E0382`",
+                    ))
+                }
+            }
+            _ => Err(syn::Error::new(
+                kind.span(),
+                "expected either `compiles`, `error` or `error E0xxx`",
+            )),
+        }
+    }
+}
+
+pub struct CompilesQuestion {
+    pub title: String,
+    pub question_text: DocString,
+    pub assets: Vec<AssetFile>,
+    pub code: Option<String>,
+    pub expected: Option<CompileResult>,
+    pub explanation: DocString,
+}
+
+impl Parse for CompilesQuestion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut res = Self {
+            title: String::new(),
+            question_text: Default::default(),
+            assets: Vec::new(),
+            code: None,
+            expected: None,
+            explanation: Default::default(),
+        };
+
+        while !input.is_empty() {
+            let field: Ident = input.parse()?;
+            let _colon: Token!(:) = input.parse()?;
+
+            match field.to_string().as_str() {
+                "title" => res.title = input.parse::<LitStr>()?.value(),
+                "question" => res.question_text = input.parse()?,
+                "code" => res.code = Some(parse_code(input)?),
+                "expect" => res.expected = Some(input.parse()?),
+                "explanation" => res.explanation = input.parse()?,
+                "asset" => res.assets.push(input.parse()?),
+                n => {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        format!("unexpected field name {}", n),
+                    ))
+                }
+            }
+
+            if input.peek(Token!(,)) {
+                let _comma = input.parse::<Token!(,)>()?;
+            }
+        }
+
+        Ok(res)
+    }
+}
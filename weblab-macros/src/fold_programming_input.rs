@@ -1,59 +1,76 @@
+use crate::attr::Target;
+use crate::performance::PerformanceTest;
 use crate::{parse_attr, Attr, ParseAttrStatus, Solution, SolutionTemplate, ALLOWED_CRATES};
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote_spanned, ToTokens};
-use syn::fold::{fold_item, Fold};
+use quote::{quote, quote_spanned};
+use syn::fold::{fold_block, fold_expr, fold_impl_item, fold_item, fold_trait_item, Fold};
+use syn::parse::{Parse, ParseStream, Parser};
 use syn::spanned::Spanned;
 use syn::{
-    Item, ItemConst, ItemEnum, ItemExternCrate, ItemFn, ItemForeignMod, ItemImpl, ItemMacro,
-    ItemMacro2, ItemMod, ItemStatic, ItemStruct, ItemTrait, ItemTraitAlias, ItemType, ItemUnion,
-    ItemUse, Macro, MacroDelimiter, UseGroup, UseName, UsePath, UseRename, UseTree,
+    Attribute, Block, Expr, ExprMacro, ExprParen, ImplItem, ImplItemMacro, ImplItemMethod, Item,
+    ItemConst, ItemEnum, ItemExternCrate, ItemFn, ItemForeignMod, ItemImpl, ItemMacro, ItemMacro2,
+    ItemMod, ItemStatic, ItemStruct, ItemTrait, ItemTraitAlias, ItemType, ItemUnion, ItemUse,
+    Macro, MacroDelimiter, Stmt, TraitItem, TraitItemMacro, TraitItemMethod, UseGroup, UseName,
+    UsePath, UseRename, UseTree,
 };
 
 pub enum FindAnnotated {
     Template {
         solution: Status,
         test: Status,
-        library: Option<ItemMod>,
+        library: Vec<ItemMod>,
     },
     Reference {
         solution: Option<ItemMod>,
         test: Option<ItemMod>,
+        performance: Vec<PerformanceTest>,
     },
 }
 
 impl FindAnnotated {
-    pub fn test(&self) -> Option<Vec<Item>> {
+    pub fn test_module(&self) -> Option<&ItemMod> {
         match self {
             FindAnnotated::Template { test, .. } => match test {
-                Status::Certain(i) => i.content.clone().map(|(_, x)| x),
-                Status::Maybe(i) => i.content.clone().map(|(_, x)| x),
+                Status::Certain(i) | Status::Maybe(i) => Some(i),
                 Status::Unkown => None,
             },
-            FindAnnotated::Reference { test, .. } => {
-                test.clone().and_then(|i| i.content).map(|(_, x)| x)
-            }
+            FindAnnotated::Reference { test, .. } => test.as_ref(),
         }
     }
 
-    pub fn solution(&self) -> Option<Vec<Item>> {
+    pub fn test(&self) -> Option<Vec<Item>> {
+        self.test_module()
+            .and_then(|i| i.content.clone())
+            .map(|(_, x)| x)
+    }
+
+    pub fn solution_module(&self) -> Option<&ItemMod> {
         match self {
             FindAnnotated::Template { solution, .. } => match solution {
-                Status::Certain(i) => i.content.clone().map(|(_, x)| x),
-                Status::Maybe(i) => i.content.clone().map(|(_, x)| x),
+                Status::Certain(i) | Status::Maybe(i) => Some(i),
                 Status::Unkown => None,
             },
-            FindAnnotated::Reference { solution, .. } => {
-                solution.clone().and_then(|i| i.content).map(|(_, x)| x)
-            }
+            FindAnnotated::Reference { solution, .. } => solution.as_ref(),
+        }
+    }
+
+    pub fn solution(&self) -> Option<Vec<Item>> {
+        self.solution_module()
+            .and_then(|i| i.content.clone())
+            .map(|(_, x)| x)
+    }
+
+    pub fn library_modules(&self) -> &[ItemMod] {
+        match self {
+            FindAnnotated::Template { library, .. } => library,
+            FindAnnotated::Reference { .. } => &[],
         }
     }
 
-    pub fn library(&self) -> Option<Vec<Item>> {
+    pub fn performance_tests(&self) -> &[PerformanceTest] {
         match self {
-            FindAnnotated::Template { library, .. } => {
-                library.clone().and_then(|i| i.content).map(|(_, x)| x)
-            }
-            FindAnnotated::Reference { .. } => None,
+            FindAnnotated::Template { .. } => &[],
+            FindAnnotated::Reference { performance, .. } => performance,
         }
     }
 
@@ -61,6 +78,7 @@ impl FindAnnotated {
         Self::Reference {
             solution: None,
             test: None,
+            performance: Vec::new(),
         }
     }
 
@@ -68,7 +86,7 @@ impl FindAnnotated {
         Self::Template {
             solution: Status::Unkown,
             test: Status::Unkown,
-            library: None,
+            library: Vec::new(),
         }
     }
 
@@ -82,6 +100,164 @@ impl FindAnnotated {
     pub fn is_template(&self) -> bool {
         !self.is_reference()
     }
+
+    /// For `template_only!{}` and `solution_only!{}` (with braces, other delimiters
+    /// are reported by `fold_macro`), whether their contents should be kept.
+    fn keep_only_contents(&self, mac: &Macro) -> Option<bool> {
+        if !matches!(mac.delimiter, MacroDelimiter::Brace(_)) {
+            return None;
+        }
+
+        let ident = mac.path.get_ident()?;
+        if ident == "template_only" {
+            Some(self.is_template())
+        } else if ident == "solution_only" {
+            Some(self.is_reference())
+        } else {
+            None
+        }
+    }
+
+    /// The contents of `template_only!{}` or `solution_only!{}` used as item or
+    /// statement, folded so nested `template_only!{}`/`solution_only!{}` and
+    /// `#[weblab(...)]` attributes in it are handled too.
+    fn fold_only_contents(&mut self, t: &TokenStream2) -> TokenStream2 {
+        if let Ok(items) = parse_many::<Item>.parse2(t.clone()) {
+            let items = items.into_iter().map(|i| self.fold_item(i));
+            return quote! {#(#items)*};
+        }
+
+        match Block::parse_within.parse2(t.clone()) {
+            Ok(stmts) => {
+                let stmts = stmts.into_iter().map(|i| self.fold_stmt(i));
+                quote! {#(#stmts)*}
+            }
+            Err(e) => e.to_compile_error(),
+        }
+    }
+
+    /// Like `fold_only_contents`, for the contents of a macro in an `impl` block.
+    fn fold_only_impl_items(&mut self, t: &TokenStream2) -> TokenStream2 {
+        match parse_many::<ImplItem>.parse2(t.clone()) {
+            Ok(items) => {
+                let items = items.into_iter().map(|i| self.fold_impl_item(i));
+                quote! {#(#items)*}
+            }
+            Err(e) => e.to_compile_error(),
+        }
+    }
+
+    /// Like `fold_only_contents`, for the contents of a macro in a trait definition.
+    fn fold_only_trait_items(&mut self, t: &TokenStream2) -> TokenStream2 {
+        match parse_many::<TraitItem>.parse2(t.clone()) {
+            Ok(items) => {
+                let items = items.into_iter().map(|i| self.fold_trait_item(i));
+                quote! {#(#items)*}
+            }
+            Err(e) => e.to_compile_error(),
+        }
+    }
+
+    /// The contents of `template_only!{}` or `solution_only!{}` in an expression.
+    /// Expressions which may bind differently to their surroundings are put between
+    /// parentheses, so `2 * solution_only!{ 1 + 2 }` stays `2 * (1 + 2)`.
+    fn fold_only_expr(&mut self, t: &TokenStream2) -> Expr {
+        match syn::parse2::<Expr>(t.clone()).map(|e| self.fold_expr(e)) {
+            Ok(
+                e @ (Expr::Lit(_)
+                | Expr::Path(_)
+                | Expr::Call(_)
+                | Expr::MethodCall(_)
+                | Expr::Macro(_)
+                | Expr::Field(_)
+                | Expr::Index(_)
+                | Expr::Paren(_)
+                | Expr::Tuple(_)
+                | Expr::Array(_)
+                | Expr::Struct(_)
+                | Expr::Block(_)
+                | Expr::Unsafe(_)
+                | Expr::Repeat(_)),
+            ) => e,
+            Ok(e) => Expr::Paren(ExprParen {
+                attrs: Vec::new(),
+                paren_token: Default::default(),
+                expr: Box::new(e),
+            }),
+            Err(e) => Expr::Verbatim(e.to_compile_error()),
+        }
+    }
+}
+
+/// Replaces the expanded contents of `template_only!{}` and `solution_only!{}`
+/// blocks (which are `Item::Verbatim`) by the items in it.
+pub fn flatten_verbatim(items: Vec<Item>) -> Vec<Item> {
+    let mut res = Vec::new();
+    for i in items {
+        match i {
+            Item::Verbatim(ts) => match parse_many::<Item>.parse2(ts.clone()) {
+                Ok(items) => res.extend(flatten_verbatim(items)),
+                Err(_) => res.push(Item::Verbatim(ts)),
+            },
+            i => res.push(i),
+        }
+    }
+    res
+}
+
+/// Removes the `#[weblab(...)]` attributes from `attrs` and returns them parsed.
+/// Doc comments are parsed too, but also kept in `attrs`. Attributes that are
+/// invalid, or can't be used on `target`, are all returned as compile errors,
+/// so the item can still be processed to find the errors in it too.
+fn split_attrs(attrs: &mut Vec<Attribute>, target: Target) -> (Vec<Attr>, TokenStream2) {
+    let mut parsed_attrs = Vec::new();
+    let mut non_parsed_attrs = Vec::new();
+    let mut errors = Vec::new();
+
+    for i in attrs.drain(..) {
+        match parse_attr(i) {
+            Ok(ParseAttrStatus::Attr(i)) => {
+                for (attr, span) in i {
+                    if let Some(message) = attr.misplaced(target) {
+                        errors.push(syn::Error::new(span, message));
+                    }
+                    parsed_attrs.push(attr);
+                }
+            }
+            Ok(ParseAttrStatus::Doc(i, a)) => {
+                parsed_attrs.push(i);
+                non_parsed_attrs.push(a);
+            }
+            Ok(ParseAttrStatus::NotParsed(a)) => {
+                non_parsed_attrs.push(a);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    *attrs = non_parsed_attrs;
+    let errors = errors.into_iter().map(|e| e.to_compile_error());
+    (parsed_attrs, quote! {#(#errors)*})
+}
+
+/// The body that replaces a function annotated with `#[weblab(stub)]` in the template.
+fn stub_block(attrs: &[Attr]) -> Option<Block> {
+    attrs.iter().find_map(|i| match i {
+        Attr::Stub(None) => Some(syn::parse_quote!({ todo!() })),
+        Attr::Stub(Some(expr)) => {
+            let expr: Expr = syn::parse_str(expr).expect("checked when parsing the attribute");
+            Some(syn::parse_quote!({ #expr }))
+        }
+        _ => None,
+    })
+}
+
+fn parse_many<T: Parse>(input: ParseStream) -> syn::Result<Vec<T>> {
+    let mut res = Vec::new();
+    while !input.is_empty() {
+        res.push(input.parse()?);
+    }
+    Ok(res)
 }
 
 struct DropUse;
@@ -102,7 +278,132 @@ impl Fold for DropUse {
     }
 }
 
+/// Whether `stmt` is `name!{ ... }` used as a statement (which syn parses as an item)
+fn is_only_stmt(stmt: Option<&Stmt>, name: &str) -> bool {
+    matches!(
+        stmt,
+        Some(Stmt::Item(Item::Macro(ItemMacro { mac, semi_token: None, .. })))
+            if mac.path.is_ident(name) && matches!(mac.delimiter, MacroDelimiter::Brace(_))
+    )
+}
+
 impl Fold for FindAnnotated {
+    // A `solution_only!{ ... }` at the end of a block (without `template_only!{}`
+    // before it) is the value of the block, so like in an expression, students
+    // fill in the missing expression in the template.
+    fn fold_block(&mut self, mut block: Block) -> Block {
+        let len = block.stmts.len();
+        if self.is_template()
+            && is_only_stmt(block.stmts.last(), "solution_only")
+            && !is_only_stmt(
+                len.checked_sub(2).and_then(|i| block.stmts.get(i)),
+                "template_only",
+            )
+        {
+            block.stmts[len - 1] = Stmt::Expr(syn::parse_quote!(todo!()));
+        }
+
+        fold_block(self, block)
+    }
+
+    // Statements like `solution_only!{ ... }` are parsed as items, and handled
+    // by `fold_item`. These are the ones in the middle of an expression,
+    // like `let x = solution_only!{ 3 };`
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        if let Expr::Macro(ExprMacro { mac, .. }) = &expr {
+            match self.keep_only_contents(mac) {
+                Some(true) => {
+                    let tokens = mac.tokens.clone();
+                    return self.fold_only_expr(&tokens);
+                }
+                // students fill in the missing expression
+                Some(false) if mac.path.is_ident("solution_only") => {
+                    return syn::parse_quote!(todo!())
+                }
+                Some(false) => {
+                    return Expr::Verbatim(quote_spanned! {
+                        mac.span() =>
+                        compile_error!("template_only!{} in an expression leaves nothing in its place in the reference solution. Put it around a whole statement, or use solution_only!{} for the reference solution's expression instead")
+                    })
+                }
+                None => {}
+            }
+        }
+
+        fold_expr(self, expr)
+    }
+
+    fn fold_impl_item(&mut self, item: ImplItem) -> ImplItem {
+        if let ImplItem::Macro(ImplItemMacro { mac, .. }) = &item {
+            match self.keep_only_contents(mac) {
+                Some(true) => {
+                    let tokens = mac.tokens.clone();
+                    return ImplItem::Verbatim(self.fold_only_impl_items(&tokens));
+                }
+                Some(false) => return ImplItem::Verbatim(TokenStream2::new()),
+                None => {}
+            }
+        }
+
+        let mut item = item;
+        let mut errors = TokenStream2::new();
+        if let ImplItem::Method(ImplItemMethod { attrs, block, .. }) = &mut item {
+            let parsed_attrs;
+            (parsed_attrs, errors) = split_attrs(attrs, Target::Function);
+
+            if let (Some(stub), true) = (stub_block(&parsed_attrs), self.is_template()) {
+                *block = stub;
+            }
+        }
+
+        let folded = fold_impl_item(self, item);
+        if errors.is_empty() {
+            folded
+        } else {
+            ImplItem::Verbatim(quote! {#errors #folded})
+        }
+    }
+
+    fn fold_trait_item(&mut self, item: TraitItem) -> TraitItem {
+        if let TraitItem::Macro(TraitItemMacro { mac, .. }) = &item {
+            match self.keep_only_contents(mac) {
+                Some(true) => {
+                    let tokens = mac.tokens.clone();
+                    return TraitItem::Verbatim(self.fold_only_trait_items(&tokens));
+                }
+                Some(false) => return TraitItem::Verbatim(TokenStream2::new()),
+                None => {}
+            }
+        }
+
+        let mut item = item;
+        let mut errors = TokenStream2::new();
+        if let TraitItem::Method(TraitItemMethod { attrs, default, .. }) = &mut item {
+            let parsed_attrs;
+            (parsed_attrs, errors) = split_attrs(attrs, Target::Function);
+
+            if let Some(stub) = stub_block(&parsed_attrs) {
+                match default {
+                    Some(block) if self.is_template() => *block = stub,
+                    Some(_) => {}
+                    None => {
+                        return TraitItem::Verbatim(quote_spanned! {
+                            item.span() =>
+                            compile_error!("#[weblab(stub)] needs a method with a body");
+                        })
+                    }
+                }
+            }
+        }
+
+        let folded = fold_trait_item(self, item);
+        if errors.is_empty() {
+            folded
+        } else {
+            TraitItem::Verbatim(quote! {#errors #folded})
+        }
+    }
+
     fn fold_macro(&mut self, mut i: Macro) -> Macro {
         const TARGETS: &[&str] = &["template_only", "solution_only"];
 
@@ -128,7 +429,12 @@ impl Fold for FindAnnotated {
     }
 
     fn fold_item(&mut self, mut item: Item) -> Item {
-        let attrs = match &mut item {
+        let target = match &item {
+            Item::Mod(_) => Target::Module,
+            Item::Fn(_) => Target::Function,
+            _ => Target::Other,
+        };
+        let (attrs, errors) = match &mut item {
             Item::Const(ItemConst { attrs, .. })
             | Item::Enum(ItemEnum { attrs, .. })
             | Item::ExternCrate(ItemExternCrate { attrs, .. })
@@ -144,47 +450,43 @@ impl Fold for FindAnnotated {
             | Item::TraitAlias(ItemTraitAlias { attrs, .. })
             | Item::Type(ItemType { attrs, .. })
             | Item::Union(ItemUnion { attrs, .. })
-            | Item::Use(ItemUse { attrs, .. }) => {
-                let mut parsed_attrs = Vec::new();
-                let mut non_parsed_attrs = Vec::new();
-
-                for i in attrs.drain(..) {
-                    match parse_attr(i) {
-                        Ok(ParseAttrStatus::Attr(i)) => parsed_attrs.extend(i),
-                        Ok(ParseAttrStatus::Doc(i, a)) => {
-                            parsed_attrs.push(i);
-                            non_parsed_attrs.push(a);
-                        }
-                        Ok(ParseAttrStatus::NotParsed(a)) => {
-                            non_parsed_attrs.push(a);
-                        }
-                        Err(e) => return Item::Verbatim(e.into()),
-                    }
-                }
-
-                *attrs = non_parsed_attrs;
-                parsed_attrs
-            }
-            Item::Verbatim(_ts) => {
-                vec![]
+            | Item::Use(ItemUse { attrs, .. }) => split_attrs(attrs, target),
+            Item::Verbatim(_ts) => (vec![], TokenStream2::new()),
+            _ => {
+                return Item::Verbatim(quote_spanned! {
+                    item.span() =>
+                    compile_error!("this kind of item isn't supported in a weblab assignment");
+                })
             }
-            _ => return Item::Verbatim(r#"compile_error!("not implemented")"#.to_token_stream()),
         };
 
         if let Item::Macro(ItemMacro { mac, .. }) = &item {
-            if let Some(ident) = mac.path.get_ident() {
-                if ident == "template_only" {
-                    if self.is_reference() {
-                        return Item::Verbatim(TokenStream2::new());
-                    } else {
-                        item = parse_only_contents(&mac.tokens);
-                    }
-                } else if ident == "solution_only" {
-                    if self.is_template() {
-                        return Item::Verbatim(TokenStream2::new());
-                    } else {
-                        item = parse_only_contents(&mac.tokens);
-                    }
+            match self.keep_only_contents(mac) {
+                Some(true) => {
+                    let tokens = mac.tokens.clone();
+                    return Item::Verbatim(self.fold_only_contents(&tokens));
+                }
+                Some(false) => return Item::Verbatim(TokenStream2::new()),
+                None => {}
+            }
+        }
+
+        // `#[weblab(stub)]` on other items is reported by `split_attrs`
+        if let (Some(stub), Item::Fn(ItemFn { block, .. }), true) =
+            (stub_block(&attrs), &mut item, self.is_template())
+        {
+            **block = stub;
+        }
+
+        if let (Item::Fn(ItemFn { sig, .. }), FindAnnotated::Reference { performance, .. }) =
+            (&item, &mut *self)
+        {
+            for i in &attrs {
+                if let Attr::Performance(max_slowdown) = i {
+                    performance.push(PerformanceTest {
+                        ident: sig.ident.clone(),
+                        max_slowdown: *max_slowdown,
+                    });
                 }
             }
         }
@@ -202,6 +504,16 @@ impl Fold for FindAnnotated {
         let without_use = DropUse.fold_item(folded.clone());
 
         if let Item::Mod(ref i) = without_use {
+            // every library module becomes its own file next to the solution and tests
+            if attrs.contains(&Attr::Library)
+                && ["solution", "test", "main"].contains(&i.ident.to_string().as_str())
+            {
+                return Item::Verbatim(quote_spanned! {
+                    i.ident.span() =>
+                    compile_error!("a library module can't be called `solution`, `test` or `main`");
+                });
+            }
+
             match self {
                 FindAnnotated::Template {
                     solution,
@@ -257,17 +569,10 @@ impl Fold for FindAnnotated {
                             }
                         }
                     } else if attrs.contains(&Attr::Library) {
-                        if library.is_none() {
-                            *library = Some(i.clone());
-                        } else {
-                            return Item::Verbatim(quote_spanned! {
-                                i.span() =>
-                                compile_error!("multiple library blocks defined")
-                            });
-                        }
+                        library.push(i.clone());
                     }
                 }
-                FindAnnotated::Reference { solution, test } => {
+                FindAnnotated::Reference { solution, test, .. } => {
                     if attrs.contains(&Attr::Solution) {
                         if solution.is_none() {
                             *solution = Some(i.clone());
@@ -291,7 +596,11 @@ impl Fold for FindAnnotated {
             }
         }
 
-        folded
+        if errors.is_empty() {
+            folded
+        } else {
+            Item::Verbatim(quote! {#errors #folded})
+        }
     }
 }
 
@@ -301,13 +610,6 @@ pub enum Status {
     Unkown,
 }
 
-fn parse_only_contents(t: &TokenStream2) -> Item {
-    // let res: Block = syn::parse2(quote! {"{ #tt }"})?;
-    // Ok(Item)
-    // TODO: parse as block so inner macros get expanded
-    Item::Verbatim(t.to_token_stream())
-}
-
 fn should_drop(t: &UseTree) -> Result<bool, String> {
     match t {
         UseTree::Path(UsePath { ident, .. })
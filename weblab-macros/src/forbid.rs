@@ -0,0 +1,72 @@
+use quote::{quote, ToTokens};
+use syn::{Lit, NestedMeta, Path};
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "type", "unsafe", "use", "where", "while",
+];
+
+/// Something students may not use in their solution, declared with
+/// `#[weblab(forbid(unsafe, "std::collections::HashMap", "println!"))]`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Forbidden {
+    Keyword(String),
+    Path(String),
+    Macro(String),
+}
+
+impl TryFrom<NestedMeta> for Forbidden {
+    type Error = syn::Error;
+
+    fn try_from(value: NestedMeta) -> Result<Self, Self::Error> {
+        match value {
+            NestedMeta::Meta(m) => {
+                if let Some(i) = m.path().get_ident() {
+                    let keyword = i.to_string();
+                    if KEYWORDS.contains(&keyword.as_str()) {
+                        return Ok(Self::Keyword(keyword));
+                    }
+                }
+
+                Err(syn::Error::new(
+                    m.path().segments[0].ident.span(),
+                    "expected a keyword, or a path or macro name in quotes \
+                    (like \"std::collections::HashMap\" or \"println!\")"
+                        .to_string(),
+                ))
+            }
+            NestedMeta::Lit(Lit::Str(s)) => {
+                let value = s.value();
+                let (name, is_macro) = match value.strip_suffix('!') {
+                    Some(name) => (name, true),
+                    None => (value.as_str(), false),
+                };
+
+                if syn::parse_str::<Path>(name).is_err() {
+                    return Err(syn::Error::new(
+                        s.span(),
+                        format!("`{name}` is not a valid path"),
+                    ));
+                }
+
+                if is_macro {
+                    Ok(Self::Macro(name.to_string()))
+                } else {
+                    Ok(Self::Path(name.to_string()))
+                }
+            }
+            NestedMeta::Lit(l) => Err(syn::Error::new(l.span(), "expected string".to_string())),
+        }
+    }
+}
+
+impl ToTokens for Forbidden {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            Forbidden::Keyword(i) => quote! {ForbiddenConstruct::Keyword(#i)},
+            Forbidden::Path(i) => quote! {ForbiddenConstruct::Path(#i)},
+            Forbidden::Macro(i) => quote! {ForbiddenConstruct::Macro(#i)},
+        })
+    }
+}
@@ -1,20 +1,39 @@
 use crate::attr::{parse_attr, parse_attr_stream, Attr, ParseAttrStatus};
 use crate::inline_question_list::InlineQuestionList;
 use crate::Attr::{Solution, SolutionTemplate};
+use cloze::{ClozeQuestion, ClozeText};
+use compiles::CompilesQuestion;
 use fold_programming_input::FindAnnotated;
+use matching::MatchingQuestion;
 use mc::McQuestion;
+use numeric::NumericQuestion;
 use open::OpenQuestion;
+use ordering::OrderingQuestion;
+use predict_output::PredictOutputQuestion;
 use proc_macro::{Span, TokenStream};
 use proc_macro2::Span as Span2;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use syn::parse_macro_input;
+use true_false::TrueFalseQuestion;
 
+mod api;
+mod asset;
 mod attr;
+mod cloze;
+mod compiles;
 mod fold_programming_input;
+mod forbid;
 mod inline_question_list;
+mod matching;
 mod mc;
+mod numeric;
 mod open;
+mod ordering;
+mod performance;
+mod predict_output;
 mod programming;
+mod source;
+mod true_false;
 
 const ALLOWED_CRATES: &[&str] = [
     "serde",
@@ -73,7 +92,11 @@ pub fn inline_question_list(item: TokenStream) -> TokenStream {
 
         #(
             pub mod #assignment_names {
-                use weblab::{mc_question, open_question};
+                use weblab::{
+                    cloze_question, compiles_question, matching_question, mc_question,
+                    numeric_question, open_question, ordering_question, predict_output_question,
+                    true_false_question,
+                };
                 #questions;
             }
         )*
@@ -85,6 +108,7 @@ pub fn open_question(item: TokenStream) -> TokenStream {
     let OpenQuestion {
         title,
         question_text,
+        assets,
         answer,
     } = parse_macro_input!(item as OpenQuestion);
 
@@ -95,14 +119,19 @@ pub fn open_question(item: TokenStream) -> TokenStream {
         return quote! {compile_error!("expected question");}.into();
     }
 
+    let track = question_text.track();
+
     quote! {
         pub mod __WEBLAB_ASSIGNMENT_METADATA {
             use weblab::*;
 
+            #track
+
             pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::Open(OpenQuestion {
                 title: #title,
 
                 assignment_text: #question_text,
+                assets: &[#(#assets),*],
                 expected_answer: #answer,
 
                 checklist: None,
@@ -117,14 +146,17 @@ pub fn mc_question(item: TokenStream) -> TokenStream {
     let McQuestion {
         title,
         question_text,
+        assets,
         options,
         num_answers_expected,
+        expect_span,
         randomize,
         explanation,
     } = parse_macro_input!(item as McQuestion);
 
     let answers: Vec<_> = options.iter().map(|i| i.text.clone()).collect();
     let corrects: Vec<_> = options.iter().map(|i| i.correct).collect();
+    let feedbacks = options.iter().map(|i| &i.feedback);
 
     if question_text.text.is_empty() {
         return quote! {compile_error!("expected question text");}.into();
@@ -144,6 +176,51 @@ pub fn mc_question(item: TokenStream) -> TokenStream {
         return quote! {compile_error!(#text);}.into();
     }
 
+    let num_correct = corrects.iter().filter(|i| **i).count();
+    if num_answers_expected != 0 && num_correct != num_answers_expected {
+        let plural = if num_answers_expected == 1 { "" } else { "s" };
+        let marked = if num_correct == 1 {
+            "1 option is".to_string()
+        } else {
+            format!("{num_correct} options are")
+        };
+        let error = if let Some(span) = expect_span {
+            syn::Error::new(
+                span,
+                format!("this question expects {num_answers_expected} answer{plural}, but {marked} marked as correct"),
+            )
+        } else {
+            let extra_option = options
+                .iter()
+                .filter(|i| i.correct)
+                .nth(num_answers_expected)
+                .expect("more correct options than expected");
+            syn::Error::new(
+                extra_option.span,
+                format!("{marked} marked as correct, but the question expects {num_answers_expected} answer{plural} (use `expect {num_correct} answers` or `multiple`)"),
+            )
+        };
+        return error.into_compile_error().into();
+    }
+
+    // proc macros can't emit warnings on stable rust,
+    // but using a deprecated constant does give a warning
+    let warnings = options.iter().enumerate().filter_map(|(idx, option)| {
+        if !options[..idx].iter().any(|i| i.text == option.text) {
+            return None;
+        }
+
+        let name = format_ident!("DUPLICATE_OPTION_{idx}", span = option.span);
+        let note = format!("the option `{}` occurs multiple times", option.text);
+        Some(quote_spanned! {option.span=>
+            #[deprecated(note = #note)]
+            const #name: () = ();
+            const _: () = #name;
+        })
+    });
+
+    let track = question_text.track();
+
     let style = if num_answers_expected == 0 {
         quote! {MCStyle::AllThatApply}
     } else {
@@ -154,16 +231,21 @@ pub fn mc_question(item: TokenStream) -> TokenStream {
         pub mod __WEBLAB_ASSIGNMENT_METADATA {
             use weblab::*;
 
+            #(#warnings)*
+            #track
+
             pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::MultipleChoice(MCQuestion {
                 title: #title,
 
                 assignment_text: #question_text,
+                assets: &[#(#assets),*],
                 explanation: #explanation,
 
                 options: &[#(
                     MCOption {
                         text: #answers,
-                        is_correct: #corrects
+                        is_correct: #corrects,
+                        feedback: #feedbacks,
                     }
                 ),*],
                 randomize: #randomize,
@@ -173,14 +255,321 @@ pub fn mc_question(item: TokenStream) -> TokenStream {
     }.into()
 }
 
+#[proc_macro]
+pub fn numeric_question(item: TokenStream) -> TokenStream {
+    let NumericQuestion {
+        title,
+        question_text,
+        assets,
+        answer,
+        tolerance,
+        explanation,
+    } = parse_macro_input!(item as NumericQuestion);
+
+    if title.is_empty() {
+        return quote! {compile_error!("expected title");}.into();
+    }
+    if question_text.text.is_empty() {
+        return quote! {compile_error!("expected question");}.into();
+    }
+    let answer = if let Some(answer) = answer {
+        answer
+    } else {
+        return quote! {compile_error!("expected an answer (using `answer: <number>`)");}.into();
+    };
+
+    quote! {
+        pub mod __WEBLAB_ASSIGNMENT_METADATA {
+            use weblab::*;
+
+            pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::Numeric(NumericQuestion {
+                title: #title,
+
+                assignment_text: #question_text,
+                assets: &[#(#assets),*],
+                explanation: #explanation,
+
+                answer: #answer,
+                tolerance: #tolerance,
+            });
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn true_false_question(item: TokenStream) -> TokenStream {
+    let TrueFalseQuestion {
+        title,
+        question_text,
+        assets,
+        answer,
+        explanation,
+    } = parse_macro_input!(item as TrueFalseQuestion);
+
+    if title.is_empty() {
+        return quote! {compile_error!("expected title");}.into();
+    }
+    if question_text.text.is_empty() {
+        return quote! {compile_error!("expected question");}.into();
+    }
+    let answer = if let Some(answer) = answer {
+        answer
+    } else {
+        return quote! {compile_error!("expected an answer (using `answer: true` or `answer: false`)");}.into();
+    };
+
+    quote! {
+        pub mod __WEBLAB_ASSIGNMENT_METADATA {
+            use weblab::*;
+
+            pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::TrueFalse(TrueFalseQuestion {
+                title: #title,
+
+                assignment_text: #question_text,
+                assets: &[#(#assets),*],
+                explanation: #explanation,
+
+                answer: #answer,
+            });
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn cloze_question(item: TokenStream) -> TokenStream {
+    let ClozeQuestion {
+        title,
+        question_text,
+        assets,
+        question_span,
+        explanation,
+    } = parse_macro_input!(item as ClozeQuestion);
+
+    if title.is_empty() {
+        return quote! {compile_error!("expected title");}.into();
+    }
+    if question_text.text.is_empty() {
+        return quote! {compile_error!("expected question");}.into();
+    }
+
+    let ClozeText { text, blanks } = match ClozeText::from_doc(&question_text) {
+        Ok(i) => i,
+        Err(e) => {
+            return syn::Error::new(question_span, e)
+                .into_compile_error()
+                .into()
+        }
+    };
+    if blanks.is_empty() {
+        return syn::Error::new(
+            question_span,
+            "expected at least one blank (like `[[answer]]`) in the question",
+        )
+        .into_compile_error()
+        .into();
+    }
+
+    quote! {
+        pub mod __WEBLAB_ASSIGNMENT_METADATA {
+            use weblab::*;
+
+            pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::Cloze(ClozeQuestion {
+                title: #title,
+
+                assignment_text: #text,
+                assets: &[#(#assets),*],
+                explanation: #explanation,
+
+                blanks: &[#(
+                    ClozeBlank {
+                        answers: &[#(#blanks),*],
+                    }
+                ),*],
+            });
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn predict_output_question(item: TokenStream) -> TokenStream {
+    let PredictOutputQuestion {
+        title,
+        question_text,
+        assets,
+        code,
+    } = parse_macro_input!(item as PredictOutputQuestion);
+
+    if title.is_empty() {
+        return quote! {compile_error!("expected title");}.into();
+    }
+    if question_text.text.is_empty() {
+        return quote! {compile_error!("expected question");}.into();
+    }
+    let code = if let Some(code) = code {
+        code
+    } else {
+        return quote! {compile_error!("expected a program (using `code: { fn main() { ... } }`)");}.into();
+    };
+
+    quote! {
+        pub mod __WEBLAB_ASSIGNMENT_METADATA {
+            use weblab::*;
+
+            pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::PredictOutput(PredictOutputQuestion {
+                title: #title,
+                assignment_text: #question_text,
+                assets: &[#(#assets),*],
+                code: #code,
+            });
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn compiles_question(item: TokenStream) -> TokenStream {
+    let CompilesQuestion {
+        title,
+        question_text,
+        assets,
+        code,
+        expected,
+        explanation,
+    } = parse_macro_input!(item as CompilesQuestion);
+
+    if title.is_empty() {
+        return quote! {compile_error!("expected title");}.into();
+    }
+    if question_text.text.is_empty() {
+        return quote! {compile_error!("expected question");}.into();
+    }
+    let code = if let Some(code) = code {
+        code
+    } else {
+        return quote! {compile_error!("expected a snippet (using `code: { ... }`)");}.into();
+    };
+    let expected = if let Some(expected) = expected {
+        expected
+    } else {
+        return quote! {compile_error!("expected whether the snippet compiles (using `expect: compiles`, `expect: error` or `expect: error E0xxx`)");}.into();
+    };
+
+    quote! {
+        pub mod __WEBLAB_ASSIGNMENT_METADATA {
+            use weblab::*;
+
+            pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::Compiles(CompilesQuestion {
+                title: #title,
+
+                assignment_text: #question_text,
+                assets: &[#(#assets),*],
+                explanation: #explanation,
+
+                code: #code,
+                expected: #expected,
+            });
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn matching_question(item: TokenStream) -> TokenStream {
+    let MatchingQuestion {
+        title,
+        question_text,
+        assets,
+        pairs,
+        explanation,
+    } = parse_macro_input!(item as MatchingQuestion);
+
+    if question_text.text.is_empty() {
+        return quote! {compile_error!("expected question text");}.into();
+    }
+    if title.is_empty() {
+        return quote! {compile_error!("expected title");}.into();
+    }
+    if pairs.len() < 2 {
+        return quote! {compile_error!("expected at least two pairs (using `pair \"left\" => \"right\"`)");}
+            .into();
+    }
+
+    let lefts = pairs.iter().map(|i| &i.left);
+    let rights = pairs.iter().map(|i| &i.right);
+
+    quote! {
+        pub mod __WEBLAB_ASSIGNMENT_METADATA {
+            use weblab::*;
+
+            pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::Matching(MatchingQuestion {
+                title: #title,
+
+                assignment_text: #question_text,
+                assets: &[#(#assets),*],
+                explanation: #explanation,
+
+                pairs: &[#(
+                    MatchingPair {
+                        left: #lefts,
+                        right: #rights,
+                    }
+                ),*],
+            });
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn ordering_question(item: TokenStream) -> TokenStream {
+    let OrderingQuestion {
+        title,
+        question_text,
+        assets,
+        steps,
+        explanation,
+    } = parse_macro_input!(item as OrderingQuestion);
+
+    if question_text.text.is_empty() {
+        return quote! {compile_error!("expected question text");}.into();
+    }
+    if title.is_empty() {
+        return quote! {compile_error!("expected title");}.into();
+    }
+    if steps.len() < 2 {
+        return quote! {compile_error!("expected at least two steps (using `step \"text\"`)");}
+            .into();
+    }
+
+    quote! {
+        pub mod __WEBLAB_ASSIGNMENT_METADATA {
+            use weblab::*;
+
+            pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::Ordering(OrderingQuestion {
+                title: #title,
+
+                assignment_text: #question_text,
+                assets: &[#(#assets),*],
+                explanation: #explanation,
+
+                steps: &[#(#steps),*],
+            });
+        }
+    }
+    .into()
+}
+
 #[proc_macro_attribute]
 pub fn weblab(attr: TokenStream, item: TokenStream) -> TokenStream {
     let attr = match parse_attr_stream(attr) {
         Ok(i) => i,
-        Err(e) => return e,
+        Err(e) => return e.to_compile_error().into(),
     };
 
-    let res = if let Some(Attr::ProgrammingAssignment) = attr.first() {
+    let res = if let Some((Attr::ProgrammingAssignment, _)) = attr.first() {
         programming::process_programming_assignment(&attr[1..], item)
     } else {
         return syn::Error::new(
@@ -0,0 +1,89 @@
+use crate::asset::AssetFile;
+use crate::open::DocString;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Token};
+
+pub struct MatchingPair {
+    pub left: String,
+    pub right: String,
+}
+
+pub struct MatchingQuestion {
+    pub title: String,
+    pub question_text: DocString,
+    pub assets: Vec<AssetFile>,
+    pub pairs: Vec<MatchingPair>,
+    pub explanation: DocString,
+}
+
+impl Parse for MatchingQuestion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut res = Self {
+            title: String::new(),
+            question_text: Default::default(),
+            assets: Vec::new(),
+            pairs: vec![],
+            explanation: Default::default(),
+        };
+
+        while !input.is_empty() {
+            let field: Ident = input.parse()?;
+
+            match field.to_string().as_str() {
+                "pair" => {
+                    let left = input.parse::<LitStr>()?;
+                    let _arrow = input.parse::<Token!(=>)>()?;
+                    let right = input.parse::<LitStr>()?;
+
+                    for i in &res.pairs {
+                        if i.left == left.value() {
+                            return Err(syn::Error::new(
+                                left.span(),
+                                format!("`{}` is already matched with `{}`", i.left, i.right),
+                            ));
+                        }
+                        if i.right == right.value() {
+                            return Err(syn::Error::new(
+                                right.span(),
+                                format!("`{}` is already matched with `{}`", i.right, i.left),
+                            ));
+                        }
+                    }
+
+                    res.pairs.push(MatchingPair {
+                        left: left.value(),
+                        right: right.value(),
+                    });
+                }
+                "explanation" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.explanation = input.parse()?
+                }
+                "title" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.title = input.parse::<LitStr>()?.value()
+                }
+                "question" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.question_text = input.parse()?
+                }
+                "asset" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.assets.push(input.parse()?)
+                }
+                n => {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        format!("unexpected field name {}", n),
+                    ))
+                }
+            }
+
+            if input.peek(Token!(,)) {
+                let _comma = input.parse::<Token!(,)>()?;
+            }
+        }
+
+        Ok(res)
+    }
+}
@@ -1,17 +1,25 @@
+use crate::asset::AssetFile;
 use crate::open::DocString;
+use proc_macro2::Span;
 use syn::parse::{Parse, ParseStream};
 use syn::{Ident, LitInt, LitStr, Token};
 
 pub struct McAnswer {
     pub text: String,
     pub correct: bool,
+    /// shown to students who picked this option
+    pub feedback: DocString,
+    pub span: Span,
 }
 
 pub struct McQuestion {
     pub title: String,
     pub question_text: DocString,
+    pub assets: Vec<AssetFile>,
     pub options: Vec<McAnswer>,
     pub num_answers_expected: usize,
+    /// where the number of expected answers was given, if it was given
+    pub expect_span: Option<Span>,
     pub randomize: bool,
     pub explanation: DocString,
 }
@@ -21,8 +29,10 @@ impl Parse for McQuestion {
         let mut res = Self {
             title: String::new(),
             question_text: Default::default(),
+            assets: Vec::new(),
             options: vec![],
             num_answers_expected: 1,
+            expect_span: None,
             randomize: false,
             explanation: Default::default(),
         };
@@ -32,22 +42,62 @@ impl Parse for McQuestion {
 
             match field.to_string().as_str() {
                 "option" => {
-                    let text = input.parse::<LitStr>()?.value();
+                    // either a string, or a doc comment for options with markdown.
+                    // Doc comments end by themselves, so no comma is needed after them
+                    let mut ends_with_doc = false;
+                    let span = input.span();
+                    let text = if input.peek(Token!(#)) {
+                        let text = input.parse::<DocString>()?.text;
+                        if text.is_empty() {
+                            return Err(syn::Error::new(span, "expected option text"));
+                        }
+                        ends_with_doc = true;
+                        text
+                    } else {
+                        input.parse::<LitStr>()?.value()
+                    };
+
                     let mut correct = false;
-                    if input.peek(Ident) {
-                        let correct_ident = input.parse::<Ident>()?;
-                        if correct_ident != "correct" {
+                    if input.peek(Ident) && !input.peek2(Token!(:)) {
+                        let correct_ident = input.fork().parse::<Ident>()?;
+                        if correct_ident == "correct" {
+                            let _correct = input.parse::<Ident>()?;
+                            correct = true;
+                            ends_with_doc = false;
+                        } else if !ends_with_doc {
                             return Err(syn::Error::new(
                                 correct_ident.span(),
-                                "expected either the ident `correct` or nothing here",
+                                "expected either the ident `correct`, `feedback:` or nothing here",
+                            ));
+                        }
+                    }
+
+                    let mut feedback = DocString::default();
+                    if input.peek(Ident) && input.peek2(Token!(:)) {
+                        let feedback_ident = input.fork().parse::<Ident>()?;
+                        if feedback_ident == "feedback" {
+                            let _feedback = input.parse::<Ident>()?;
+                            let _colon: Token!(:) = input.parse()?;
+                            feedback = input.parse()?;
+                            ends_with_doc = true;
+                        } else if !ends_with_doc {
+                            return Err(syn::Error::new(
+                                feedback_ident.span(),
+                                "expected either `feedback:` or nothing here",
                             ));
                         }
-                        correct = true;
                     }
 
-                    let _comma = input.parse::<Token!(,)>()?;
+                    if !ends_with_doc {
+                        let _comma = input.parse::<Token!(,)>()?;
+                    }
 
-                    res.options.push(McAnswer { text, correct })
+                    res.options.push(McAnswer {
+                        text,
+                        correct,
+                        feedback,
+                        span,
+                    })
                 }
                 "explanation" => {
                     let _colon: Token!(:) = input.parse()?;
@@ -58,13 +108,16 @@ impl Parse for McQuestion {
                 }
                 "multiple" => {
                     res.num_answers_expected = 0;
+                    res.expect_span = Some(field.span());
                 }
                 "title" => {
                     let _colon: Token!(:) = input.parse()?;
                     res.title = input.parse::<LitStr>()?.value()
                 }
                 "expect" => {
-                    res.num_answers_expected = input.parse::<LitInt>()?.base10_parse()?;
+                    let num = input.parse::<LitInt>()?;
+                    res.num_answers_expected = num.base10_parse()?;
+                    res.expect_span = Some(field.span().join(num.span()).unwrap_or(num.span()));
 
                     let answers = input.parse::<Ident>()?;
                     if answers != "answers" {
@@ -78,6 +131,14 @@ impl Parse for McQuestion {
                     let _colon: Token!(:) = input.parse()?;
                     res.question_text = input.parse()?
                 }
+                "question_file" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.question_text = DocString::parse_file(input)?
+                }
+                "asset" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.assets.push(input.parse()?)
+                }
                 n => {
                     return Err(syn::Error::new(
                         field.span(),
@@ -0,0 +1,76 @@
+use crate::asset::AssetFile;
+use crate::open::DocString;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, Lit, LitStr, Token};
+
+pub struct NumericQuestion {
+    pub title: String,
+    pub question_text: DocString,
+    pub assets: Vec<AssetFile>,
+    pub answer: Option<f64>,
+    pub tolerance: f64,
+    pub explanation: DocString,
+}
+
+/// parses an integer or float literal, optionally preceded by a minus sign
+fn parse_number(input: ParseStream) -> syn::Result<f64> {
+    let negative = if input.peek(Token!(-)) {
+        let _minus = input.parse::<Token!(-)>()?;
+        true
+    } else {
+        false
+    };
+
+    let value = match input.parse::<Lit>()? {
+        Lit::Int(i) => i.base10_parse::<f64>()?,
+        Lit::Float(f) => f.base10_parse::<f64>()?,
+        l => return Err(syn::Error::new(l.span(), "expected a number")),
+    };
+
+    Ok(if negative { -value } else { value })
+}
+
+impl Parse for NumericQuestion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut res = Self {
+            title: String::new(),
+            question_text: Default::default(),
+            assets: Vec::new(),
+            answer: None,
+            tolerance: 0.0,
+            explanation: Default::default(),
+        };
+
+        while !input.is_empty() {
+            let field: Ident = input.parse()?;
+            let _colon: Token!(:) = input.parse()?;
+
+            match field.to_string().as_str() {
+                "title" => res.title = input.parse::<LitStr>()?.value(),
+                "question" => res.question_text = input.parse()?,
+                "answer" => res.answer = Some(parse_number(input)?),
+                "tolerance" => {
+                    let span = input.span();
+                    res.tolerance = parse_number(input)?;
+                    if res.tolerance < 0.0 {
+                        return Err(syn::Error::new(span, "tolerance can't be negative"));
+                    }
+                }
+                "explanation" => res.explanation = input.parse()?,
+                "asset" => res.assets.push(input.parse()?),
+                n => {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        format!("unexpected field name {}", n),
+                    ))
+                }
+            }
+
+            if input.peek(Token!(,)) {
+                let _comma = input.parse::<Token!(,)>()?;
+            }
+        }
+
+        Ok(res)
+    }
+}
@@ -1,11 +1,120 @@
-use quote::ToTokens;
+use crate::asset::AssetFile;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use std::fs;
+use std::path::{Path, PathBuf};
 use syn::parse::{Parse, ParseStream};
 use syn::spanned::Spanned;
 use syn::{Attribute, Ident, Lit, LitStr, Meta, MetaNameValue, Token};
+use weblab_assignment_structure::markdown::{image_paths, is_relative, normalize_path};
 
 #[derive(Default)]
 pub struct DocString {
     pub text: String,
+    /// the file the text was read from, if it comes from `question_file: "..."`
+    pub file: Option<String>,
+}
+
+impl DocString {
+    /// Parses the path of a markdown file (relative to the crate root) and reads it
+    pub fn parse_file(input: ParseStream) -> syn::Result<Self> {
+        let path: LitStr = input.parse()?;
+        let (file, text) = read_markdown_file(&path)?;
+
+        Ok(Self {
+            text,
+            file: Some(file),
+        })
+    }
+
+    /// Makes the crate rebuild when the file this text was read from changes
+    pub fn track(&self) -> TokenStream2 {
+        self.file.as_deref().map(track_file).unwrap_or_default()
+    }
+}
+
+/// The path of a file relative to the root of the crate the macro is used in
+pub fn manifest_path(path: &LitStr) -> syn::Result<PathBuf> {
+    let root = std::env::var("CARGO_MANIFEST_DIR").map_err(|_| {
+        syn::Error::new(
+            path.span(),
+            "CARGO_MANIFEST_DIR is not set, files can only be read when building with cargo",
+        )
+    })?;
+
+    Ok(Path::new(&root).join(path.value()))
+}
+
+/// Reads a file relative to the root of the crate the macro is used in.
+/// Returns the absolute path of the file, and its (trimmed) contents.
+pub fn read_manifest_file(path: &LitStr) -> syn::Result<(String, String)> {
+    let full_path = manifest_path(path)?;
+    let text = fs::read_to_string(&full_path).map_err(|e| {
+        syn::Error::new(
+            path.span(),
+            format!("couldn't read {}: {}", full_path.display(), e),
+        )
+    })?;
+
+    Ok((
+        full_path.to_string_lossy().into_owned(),
+        text.trim().to_string(),
+    ))
+}
+
+/// Reads a markdown file like `read_manifest_file`, and makes the images in it that
+/// are relative to the file relative to the crate root, like the paths of assets.
+pub fn read_markdown_file(path: &LitStr) -> syn::Result<(String, String)> {
+    let (full_path, text) = read_manifest_file(path)?;
+    let dir = Path::new(&path.value())
+        .parent()
+        .map(|i| i.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut res = String::new();
+    let mut last_end = 0;
+    for range in image_paths(&text) {
+        let image = &text[range.clone()];
+        if !is_relative(image) {
+            continue;
+        }
+
+        let from_file = normalize_path(&format!("{dir}/{image}"));
+        let from_root = normalize_path(image);
+        let exists =
+            |i: &str| manifest_path(&LitStr::new(i, path.span())).is_ok_and(|i| i.is_file());
+        if from_file != from_root && exists(&from_file) && exists(&from_root) {
+            return Err(syn::Error::new(
+                path.span(),
+                format!(
+                    "the image `{image}` in {full_path} is ambiguous, both `{from_file}` \
+                    (relative to the markdown file) and `{from_root}` (relative to the crate \
+                    root) exist"
+                ),
+            ));
+        }
+
+        // images that don't exist are reported by the cli when they're not an asset
+        let resolved = if exists(&from_root) && !exists(&from_file) {
+            from_root
+        } else {
+            from_file
+        };
+        res.push_str(&text[last_end..range.start]);
+        res.push_str(&resolved);
+        last_end = range.end;
+    }
+    res.push_str(&text[last_end..]);
+
+    Ok((full_path, res))
+}
+
+/// Includes the file in the generated code, so cargo knows to
+/// rebuild when it changes
+pub fn track_file(path: &str) -> TokenStream2 {
+    quote! {
+        const _: &str = include_str!(#path);
+    }
 }
 
 impl ToTokens for DocString {
@@ -17,20 +126,14 @@ impl ToTokens for DocString {
 impl Parse for DocString {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let attrs = Attribute::parse_outer(input)?;
-        let mut text = String::new();
+        let mut lines = Vec::new();
 
         for i in attrs {
             let meta = i.parse_meta()?;
             match meta {
                 Meta::NameValue(MetaNameValue { path, lit, .. }) if path.is_ident("doc") => {
                     match lit {
-                        Lit::Str(val) => {
-                            if !text.is_empty() {
-                                text.push('\n');
-                            }
-
-                            text.push_str(val.value().as_str().trim_start())
-                        }
+                        Lit::Str(val) => lines.extend(val.value().split('\n').map(str::to_string)),
                         _ => {
                             return Err(syn::Error::new(lit.span(), "expected string literal here"))
                         }
@@ -40,13 +143,43 @@ impl Parse for DocString {
             }
         }
 
-        Ok(Self { text })
+        Ok(Self {
+            text: unindent(&lines),
+            file: None,
+        })
     }
 }
 
+/// Removes the indentation all lines have in common (usually the space after `///`),
+/// but keeps the rest, so indented code and nested lists keep their formatting.
+pub fn unindent(lines: &[String]) -> String {
+    // the whitespace itself is compared, since it can be more than a byte (like `\u{a0}`)
+    let indent = lines
+        .iter()
+        .filter(|i| !i.trim().is_empty())
+        .map(|i| &i[..i.len() - i.trim_start().len()])
+        .reduce(|common, indent| {
+            let len = common
+                .char_indices()
+                .zip(indent.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((i, c), _)| i + c.len_utf8());
+            &common[..len]
+        })
+        .unwrap_or("");
+
+    lines
+        .iter()
+        .map(|i| i.strip_prefix(indent).unwrap_or_default().trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 pub struct OpenQuestion {
     pub title: String,
     pub question_text: DocString,
+    pub assets: Vec<AssetFile>,
     pub answer: DocString,
 }
 
@@ -55,6 +188,7 @@ impl Parse for OpenQuestion {
         let mut res = Self {
             title: String::new(),
             question_text: Default::default(),
+            assets: Vec::new(),
             answer: Default::default(),
         };
 
@@ -64,7 +198,9 @@ impl Parse for OpenQuestion {
             match field.to_string().as_str() {
                 "title" => res.title = input.parse::<LitStr>()?.value(),
                 "question" => res.question_text = input.parse()?,
+                "question_file" => res.question_text = DocString::parse_file(input)?,
                 "answer" => res.answer = input.parse()?,
+                "asset" => res.assets.push(input.parse()?),
                 n => {
                     return Err(syn::Error::new(
                         field.span(),
@@ -0,0 +1,71 @@
+use crate::asset::AssetFile;
+use crate::open::DocString;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Token};
+
+pub struct OrderingQuestion {
+    pub title: String,
+    pub question_text: DocString,
+    pub assets: Vec<AssetFile>,
+    /// in the correct order
+    pub steps: Vec<String>,
+    pub explanation: DocString,
+}
+
+impl Parse for OrderingQuestion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut res = Self {
+            title: String::new(),
+            question_text: Default::default(),
+            assets: Vec::new(),
+            steps: vec![],
+            explanation: Default::default(),
+        };
+
+        while !input.is_empty() {
+            let field: Ident = input.parse()?;
+
+            match field.to_string().as_str() {
+                "step" => {
+                    let step = input.parse::<LitStr>()?;
+                    if res.steps.contains(&step.value()) {
+                        return Err(syn::Error::new(
+                            step.span(),
+                            "this step already occurs, so the correct order would be ambiguous",
+                        ));
+                    }
+
+                    res.steps.push(step.value());
+                }
+                "explanation" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.explanation = input.parse()?
+                }
+                "title" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.title = input.parse::<LitStr>()?.value()
+                }
+                "question" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.question_text = input.parse()?
+                }
+                "asset" => {
+                    let _colon: Token!(:) = input.parse()?;
+                    res.assets.push(input.parse()?)
+                }
+                n => {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        format!("unexpected field name {}", n),
+                    ))
+                }
+            }
+
+            if input.peek(Token!(,)) {
+                let _comma = input.parse::<Token!(,)>()?;
+            }
+        }
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,236 @@
+use crate::fold_programming_input::flatten_verbatim;
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{Item, ItemFn, ItemUse, UseGroup, UsePath, UseRename, UseTree};
+
+/// The factor a solution may be slower than the reference solution when
+/// `#[weblab(performance)]` is used without an explicit factor.
+pub const DEFAULT_MAX_SLOWDOWN: f64 = 2.0;
+
+/// Prefix of the line a performance test prints. cargo-weblabtest looks for
+/// this prefix to find the measurements in the test output.
+const REPORT_PREFIX: &str = "weblab-performance ";
+
+pub struct PerformanceTest {
+    pub ident: Ident,
+    pub max_slowdown: f64,
+}
+
+/// Rewrites every test marked with `#[weblab(performance)]` so it runs its body
+/// against both the reference solution and the student's solution, and prints
+/// the time both took. Only used for the spec test that is exported to weblab,
+/// locally these tests just run like any other test.
+///
+/// To be able to run the reference solution on weblab, a copy of it is embedded
+/// in a private module of the spec test. Inside the body of a performance test,
+/// `solution` and everything the spec test imports from it are shadowed to refer
+/// to that copy while timing the reference. The `library` modules are imported
+/// next to the copy, so its `super::` paths to them still resolve.
+pub fn instrument_performance_tests(
+    test: Vec<Item>,
+    solution: &[Item],
+    library: &[Ident],
+    performance_tests: &[PerformanceTest],
+) -> syn::Result<Vec<Item>> {
+    if performance_tests.is_empty() {
+        return Ok(test);
+    }
+    let mut test = flatten_verbatim(test);
+    let reference_imports = test
+        .iter()
+        .flat_map(|i| match i {
+            Item::Use(ItemUse { tree, .. }) => reference_imports(tree),
+            _ => Vec::new(),
+        })
+        .collect::<Vec<_>>();
+
+    for PerformanceTest {
+        ident,
+        max_slowdown,
+    } in performance_tests
+    {
+        let f = test
+            .iter_mut()
+            .find_map(|i| match i {
+                Item::Fn(f) if f.sig.ident == *ident => Some(f),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                syn::Error::new(
+                    ident.span(),
+                    "#[weblab(performance)] can only be used on functions in the spec test module",
+                )
+            })?;
+
+        let ItemFn {
+            attrs,
+            vis,
+            sig,
+            block,
+        } = f.clone();
+        check_solution_paths(block.to_token_stream())?;
+
+        *f = syn::parse_quote! {
+            #(#attrs)*
+            #vis #sig {
+                __weblab_performance::measure(
+                    concat!(module_path!(), "::", stringify!(#ident)),
+                    #max_slowdown,
+                    || {
+                        #[allow(unused_imports)]
+                        use self::__weblab_reference::solution;
+                        #(
+                            #[allow(unused_imports)]
+                            use #reference_imports;
+                        )*
+                        #block
+                    },
+                    || #block,
+                )
+            }
+        };
+    }
+
+    // a solution that imports a library module itself can't import it twice
+    let defined = solution.iter().flat_map(type_names).collect::<Vec<_>>();
+    let solution_library = library.iter().filter(|i| !defined.contains(i));
+    test.push(syn::parse_quote! {
+        #[allow(dead_code, unused_imports)]
+        mod __weblab_reference {
+            #(use crate::#library;)*
+
+            pub mod solution {
+                #(use crate::#solution_library;)*
+
+                #(#solution)*
+            }
+        }
+    });
+
+    test.push(syn::parse_quote! {
+        mod __weblab_performance {
+            use std::time::{Duration, Instant};
+
+            const ROUNDS: usize = 5;
+
+            // Alternately runs the reference and the solution a few times, and reports
+            // the fastest run of both. Returns whatever the solution's run returned.
+            pub fn measure<R, S>(
+                name: &str,
+                max_slowdown: f64,
+                mut reference: impl FnMut() -> R,
+                mut solution: impl FnMut() -> S,
+            ) -> S {
+                let mut reference_time = Duration::MAX;
+                let mut solution_time = Duration::MAX;
+                let mut result = None;
+
+                for _ in 0..ROUNDS {
+                    let start = Instant::now();
+                    let _ = reference();
+                    reference_time = reference_time.min(start.elapsed());
+
+                    let start = Instant::now();
+                    result = Some(solution());
+                    solution_time = solution_time.min(start.elapsed());
+                }
+
+                // libtest names tests without the crate name
+                let name = name.split_once("::").map_or(name, |(_, rest)| rest);
+                println!(
+                    "{}{{\"name\":\"{}\",\"reference_ns\":{},\"solution_ns\":{},\"max_slowdown\":{:?}}}",
+                    #REPORT_PREFIX,
+                    name,
+                    reference_time.as_nanos(),
+                    solution_time.as_nanos(),
+                    max_slowdown,
+                );
+
+                result.expect("ran at least once")
+            }
+        }
+    });
+
+    Ok(test)
+}
+
+/// The imports of (items in) the solution in `tree`, a `use` in the spec test,
+/// changed to import the same from the reference solution. The import of the
+/// `solution` module itself is always shadowed, so that one is left out.
+fn reference_imports(tree: &UseTree) -> Vec<UseTree> {
+    match tree {
+        UseTree::Path(UsePath { ident, tree, .. }) if ident == "solution" => {
+            let tree = match tree.as_ref() {
+                UseTree::Name(i) if i.ident == "self" => return Vec::new(),
+                UseTree::Group(UseGroup { items, .. }) => {
+                    let items = items
+                        .iter()
+                        .filter(|i| !matches!(i, UseTree::Name(i) if i.ident == "self"));
+                    syn::parse_quote!({#(#items),*})
+                }
+                tree => tree.clone(),
+            };
+            vec![syn::parse_quote!(self::__weblab_reference::solution::#tree)]
+        }
+        UseTree::Rename(UseRename { ident, rename, .. }) if ident == "solution" => {
+            vec![syn::parse_quote!(self::__weblab_reference::solution as #rename)]
+        }
+        UseTree::Path(UsePath { tree, .. }) => reference_imports(tree),
+        UseTree::Group(UseGroup { items, .. }) => {
+            items.iter().flat_map(reference_imports).collect()
+        }
+        UseTree::Name(_) | UseTree::Rename(_) | UseTree::Glob(_) => Vec::new(),
+    }
+}
+
+/// The names `item` defines or imports in the type namespace, which is where
+/// modules live.
+fn type_names(item: &Item) -> Vec<&Ident> {
+    fn use_names(tree: &UseTree) -> Vec<&Ident> {
+        match tree {
+            UseTree::Path(UsePath { tree, .. }) => use_names(tree),
+            UseTree::Name(i) => vec![&i.ident],
+            UseTree::Rename(UseRename { rename, .. }) => vec![rename],
+            UseTree::Glob(_) => Vec::new(),
+            UseTree::Group(UseGroup { items, .. }) => items.iter().flat_map(use_names).collect(),
+        }
+    }
+
+    match item {
+        Item::Use(ItemUse { tree, .. }) => use_names(tree),
+        Item::Mod(i) => vec![&i.ident],
+        Item::Struct(i) => vec![&i.ident],
+        Item::Enum(i) => vec![&i.ident],
+        Item::Union(i) => vec![&i.ident],
+        Item::Trait(i) => vec![&i.ident],
+        Item::Type(i) => vec![&i.ident],
+        _ => Vec::new(),
+    }
+}
+
+/// Paths like `super::solution::f` always refer to the student's solution, so
+/// a performance test using them would time the student's solution twice.
+fn check_solution_paths(tokens: TokenStream) -> syn::Result<()> {
+    let mut colons = 0;
+    for tt in tokens {
+        match &tt {
+            TokenTree::Ident(i) if i == "solution" && colons >= 2 => {
+                return Err(syn::Error::new(
+                    i.span(),
+                    "in a performance test, refer to the solution as `solution::...` (after \
+                    `use super::solution`), or import its items with `use`, so the reference \
+                    solution can be swapped in while measuring",
+                ));
+            }
+            TokenTree::Group(g) => check_solution_paths(g.stream())?,
+            _ => {}
+        }
+
+        colons = match &tt {
+            TokenTree::Punct(p) if p.as_char() == ':' => colons + 1,
+            _ => 0,
+        };
+    }
+
+    Ok(())
+}
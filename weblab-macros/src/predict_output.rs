@@ -0,0 +1,65 @@
+use crate::asset::AssetFile;
+use crate::open::DocString;
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, File, Ident, LitStr, Token};
+
+pub struct PredictOutputQuestion {
+    pub title: String,
+    pub question_text: DocString,
+    pub assets: Vec<AssetFile>,
+    /// The program students have to predict the output of. It's only
+    /// compiled and run by the cli when generating the assignment.
+    pub code: Option<String>,
+}
+
+/// Either a block with rust code (`{ fn main() { ... } }`), or a string
+/// literal with the code, which keeps comments and formatting intact.
+pub fn parse_code(input: ParseStream) -> syn::Result<String> {
+    if input.peek(LitStr) {
+        let code = input.parse::<LitStr>()?;
+        syn::parse_str::<File>(&code.value())
+            .map_err(|e| syn::Error::new(code.span(), format!("invalid rust code: {e}")))?;
+        return Ok(code.value());
+    }
+
+    let content;
+    let _braces = braced!(content in input);
+    let file: File = content.parse()?;
+    Ok(file.into_token_stream().to_string())
+}
+
+impl Parse for PredictOutputQuestion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut res = Self {
+            title: String::new(),
+            question_text: Default::default(),
+            assets: Vec::new(),
+            code: None,
+        };
+
+        while !input.is_empty() {
+            let field: Ident = input.parse()?;
+            let _colon: Token!(:) = input.parse()?;
+
+            match field.to_string().as_str() {
+                "title" => res.title = input.parse::<LitStr>()?.value(),
+                "question" => res.question_text = input.parse()?,
+                "code" => res.code = Some(parse_code(input)?),
+                "asset" => res.assets.push(input.parse()?),
+                n => {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        format!("unexpected field name {}", n),
+                    ))
+                }
+            }
+
+            if input.peek(Token!(,)) {
+                let _comma = input.parse::<Token!(,)>()?;
+            }
+        }
+
+        Ok(res)
+    }
+}
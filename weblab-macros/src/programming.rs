@@ -1,13 +1,25 @@
+use crate::api::public_api;
+use crate::attr::{collect_errors, Target};
+use crate::open::{read_manifest_file, track_file};
+use crate::performance::instrument_performance_tests;
+use crate::source::{render_items, splice_files};
 use crate::{parse_attr, Attr, FindAnnotated, ParseAttrStatus};
 use proc_macro::TokenStream;
-use quote::quote;
+use proc_macro2::{Group, Span as Span2, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote};
 use std::mem;
+use std::str::FromStr;
 use syn::fold::Fold;
-use syn::ItemMod;
+use syn::spanned::Spanned;
+use syn::{File, Item, ItemMod, LitStr};
 
-pub fn process_programming_assignment(attributes: &[Attr], item: TokenStream) -> TokenStream {
+pub fn process_programming_assignment(
+    attributes: &[(Attr, Span2)],
+    item: TokenStream,
+) -> TokenStream {
     let mut attrs = attributes.to_vec();
     let mut non_parsed_attrs = Vec::new();
+    let mut errors = Vec::new();
 
     let mut module = syn::parse_macro_input!(item as ItemMod);
 
@@ -16,21 +28,36 @@ pub fn process_programming_assignment(attributes: &[Attr], item: TokenStream) ->
         match parse_attr(i) {
             Ok(ParseAttrStatus::Attr(i)) => attrs.extend(i),
             Ok(ParseAttrStatus::Doc(i, a)) => {
-                attrs.push(i);
+                attrs.push((i, a.span()));
                 non_parsed_attrs.push(a);
             }
             Ok(ParseAttrStatus::NotParsed(a)) => {
                 non_parsed_attrs.push(a);
             }
-            Err(e) => return e,
+            Err(e) => errors.push(e),
         }
     }
 
+    for (attr, span) in &attrs {
+        if let Some(message) = attr.misplaced(Target::Assignment) {
+            errors.push(syn::Error::new(*span, message));
+        }
+    }
+    let attrs = attrs.into_iter().map(|(i, _)| i).collect::<Vec<_>>();
+
+    let loaded_files = match load_module_files(&mut module) {
+        Ok(i) => i,
+        Err(e) => {
+            errors.push(e);
+            Vec::new()
+        }
+    };
+
     let mut reference = FindAnnotated::reference();
     let mut template = FindAnnotated::template();
 
     let reference_modified = reference.fold_item_mod(module.clone());
-    let _ = template.fold_item_mod(module); // for side effects
+    let _ = template.fold_item_mod(module.clone()); // for side effects
 
     let mut title = reference_modified.ident.to_string();
     let mut num_titles = 0;
@@ -41,52 +68,92 @@ pub fn process_programming_assignment(attributes: &[Attr], item: TokenStream) ->
         }
     }
 
+    let span = reference_modified.ident.span();
     if num_titles > 1 {
-        return quote! {
-            compile_error!("assignment has more than one title");
-        }
-        .into();
+        errors.push(syn::Error::new(span, "assignment has more than one title"));
+    }
+    let spectest = reference.test();
+    if spectest.is_none() {
+        errors.push(syn::Error::new(
+            span,
+            "assignment has no spectest, add a module with #[weblab(test)]",
+        ));
+    }
+    let reference_solution = reference.solution();
+    if reference_solution.is_none() {
+        errors.push(syn::Error::new(
+            span,
+            "assignment has no reference solution, add a module with #[weblab(solution)]",
+        ));
     }
 
-    let spectest = if let Some(i) = reference.test().map(|i| quote! {#(#i)*}.to_string()) {
-        i
-    } else {
-        return quote! {
-            compile_error!("assignment has no spectest");
-        }
-        .into();
-    };
-    let testtemplate = template
-        .test()
-        .map(|i| quote! {#(#i)*}.to_string())
-        .unwrap_or_else(|| "".to_string());
-    let referencesolution =
-        if let Some(i) = reference.solution().map(|i| quote! {#(#i)*}.to_string()) {
-            i
-        } else {
+    let errors = collect_errors(errors.into_iter().map(Err::<(), _>));
+    let (spectest, reference_solution) = match (spectest, reference_solution, errors) {
+        (Some(spectest), Some(reference_solution), Ok(_)) => (spectest, reference_solution),
+        (.., errors) => {
+            let errors = errors.err().map(|e| e.to_compile_error());
+            // the errors inside the assignment are in `reference_modified`, so they're reported too
             return quote! {
-                compile_error!("assignment has no reference solution");
+                #errors
+
+                #[allow(unused_imports)]
+                #[allow(dead_code)]
+                #reference_modified
             }
             .into();
-        };
-    let solutiontemplate = template
-        .solution()
-        .map(|i| quote! {#(#i)*}.to_string())
-        .unwrap_or_else(|| "".to_string());
-    let library = if let Some(i) = template.library().map(|i| quote! {#(#i)*}.to_string()) {
-        quote! {
-            Some(#i)
         }
+    };
+    let api = public_api(&template.solution().unwrap_or_default());
+    let rendered = match render(&reference, &template, spectest, &reference_solution) {
+        Ok(rendered) => rendered,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    // the tokens read from files don't know where in the file they are, so
+    // their comments and formatting are recovered by rendering the source again
+    let rendered = if loaded_files.is_empty() {
+        rendered
     } else {
-        quote! {
-            None
-        }
+        source_with_files(&module, &loaded_files)
+            .and_then(|text| render_source(&text))
+            .unwrap_or(rendered)
     };
+    let Rendered {
+        spectest,
+        test_template: testtemplate,
+        solution: referencesolution,
+        solution_template: solutiontemplate,
+        library,
+    } = rendered;
+    let library = library
+        .iter()
+        .map(|(name, contents)| {
+            quote! {
+                SourceFile {
+                    name: #name,
+                    contents: #contents,
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let clippy = attrs.contains(&Attr::Clippy);
+    let require_fmt = attrs.contains(&Attr::RequireFmt);
+    let forbidden = attrs
+        .iter()
+        .filter_map(|x| {
+            if let Attr::Forbid(i) = x {
+                Some(i)
+            } else {
+                None
+            }
+        })
+        .flatten()
+        .collect::<Vec<_>>();
 
     let assignment_text = attrs
         .iter()
         .filter_map(|x| {
-            if let Attr::Doc(i) = x {
+            if let Attr::Doc(i) | Attr::DocFile { text: i, .. } = x {
                 Some(i.as_str())
             } else {
                 None
@@ -95,23 +162,52 @@ pub fn process_programming_assignment(attributes: &[Attr], item: TokenStream) ->
         .map(|i| i.trim())
         .collect::<Vec<_>>()
         .join("\n");
+    let assets = attrs
+        .iter()
+        .filter_map(|x| {
+            if let Attr::Asset(i) = x {
+                Some(i)
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    let tracked_files = attrs
+        .iter()
+        .filter_map(|x| {
+            if let Attr::DocFile { path, .. } = x {
+                Some(path)
+            } else {
+                None
+            }
+        })
+        .chain(loaded_files.iter().map(|i| &i.path))
+        .map(|i| track_file(i))
+        .collect::<Vec<_>>();
 
     quote! {
         pub mod __WEBLAB_ASSIGNMENT_METADATA {
             use weblab::*;
 
+            #(#tracked_files)*
+
             pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::Programming(ProgrammingAssignment {
                 title: #title,
 
                 assignment_text: #assignment_text,
+                assets: &[#(#assets),*],
 
                 library_visible: false,
                 spectest_stdout_visible: false,
+                clippy: #clippy,
+                require_fmt: #require_fmt,
+                forbidden: &[#(#forbidden),*],
+                api: &[#(#api),*],
 
                 test: #spectest,
                 solution: #referencesolution,
 
-                library: #library,
+                library: &[#(#library),*],
                 test_template: #testtemplate,
                 solution_template: #solutiontemplate,
 
@@ -124,3 +220,201 @@ pub fn process_programming_assignment(attributes: &[Attr], item: TokenStream) ->
         #reference_modified
     }.into()
 }
+
+/// The source code of an assignment, as it's shown on WebLab
+struct Rendered {
+    spectest: String,
+    test_template: String,
+    solution: String,
+    solution_template: String,
+    /// the name and contents of every library module
+    library: Vec<(String, String)>,
+}
+
+fn render(
+    reference: &FindAnnotated,
+    template: &FindAnnotated,
+    spectest: Vec<Item>,
+    reference_solution: &[Item],
+) -> syn::Result<Rendered> {
+    // without library modules, the runner still has an (empty) `library` module
+    let library = match template.library_modules() {
+        [] => vec![format_ident!("library")],
+        modules => modules.iter().map(|i| i.ident.clone()).collect(),
+    };
+    let spectest = instrument_performance_tests(
+        spectest,
+        reference_solution,
+        &library,
+        reference.performance_tests(),
+    )?;
+
+    Ok(Rendered {
+        spectest: render_items(&spectest, reference.test_module()),
+        test_template: template
+            .test()
+            .map(|i| render_items(&i, template.test_module()))
+            .unwrap_or_default(),
+        solution: render_items(reference_solution, reference.solution_module()),
+        solution_template: template
+            .solution()
+            .map(|i| render_items(&i, template.solution_module()))
+            .unwrap_or_default(),
+        library: template
+            .library_modules()
+            .iter()
+            .map(|i| {
+                let contents = render_items(
+                    &i.content.clone().map(|(_, x)| x).unwrap_or_default(),
+                    Some(i),
+                );
+                (i.ident.to_string(), contents)
+            })
+            .collect(),
+    })
+}
+
+/// Uses proc-macro2's own implementation of spans while it's alive. Its spans
+/// know where they are in strings parsed at runtime, unlike the compiler's.
+/// Tokens made while it's alive can't be mixed with the compiler's tokens.
+struct Fallback;
+
+impl Fallback {
+    fn force() -> Self {
+        proc_macro2::fallback::force();
+        Self
+    }
+}
+
+impl Drop for Fallback {
+    fn drop(&mut self) {
+        proc_macro2::fallback::unforce();
+    }
+}
+
+/// Renders the assignment from `text`, the source of its module with the
+/// contents of files filled in, so the comments in those files are kept.
+fn render_source(text: &str) -> Option<Rendered> {
+    let _fallback = Fallback::force();
+
+    let module: ItemMod = syn::parse_str(text).ok()?;
+    let mut reference = FindAnnotated::reference();
+    let mut template = FindAnnotated::template();
+    let _ = reference.fold_item_mod(module.clone());
+    let _ = template.fold_item_mod(module);
+
+    let spectest = reference.test()?;
+    let reference_solution = reference.solution()?;
+    render(&reference, &template, spectest, &reference_solution).ok()
+}
+
+/// The source of `module` as it was written, with the contents of the loaded
+/// files in their (empty) modules.
+fn source_with_files(module: &ItemMod, files: &[LoadedFile]) -> Option<String> {
+    let (brace, _) = module.content.as_ref()?;
+    let files = files
+        .iter()
+        .map(|i| (i.brace, i.text.as_str()))
+        .collect::<Vec<_>>();
+    let text = splice_files(brace.span, &files)?;
+
+    Some(format!("mod {} {text}", module.ident))
+}
+
+/// A file read into a module with `file = "..."`
+struct LoadedFile {
+    path: String,
+    text: String,
+    /// the span of the braces of the module in the assignment
+    brace: Span2,
+}
+
+/// Attribute macros can't see the contents of modules in other files (`mod solution;`),
+/// so those are written as `#[weblab(solution, file = "src/solution.rs")] mod solution {}`.
+/// This reads those files into their modules, and returns what was read.
+fn load_module_files(module: &mut ItemMod) -> syn::Result<Vec<LoadedFile>> {
+    let Some((_, items)) = &mut module.content else {
+        return Ok(Vec::new());
+    };
+
+    let loaded = collect_errors(items.iter_mut().filter_map(|item| match item {
+        Item::Mod(submodule) => load_module_file(submodule).transpose(),
+        _ => None,
+    }))?;
+    Ok(loaded)
+}
+
+/// Reads the file of `module`, if it has `file = "..."`
+fn load_module_file(module: &mut ItemMod) -> syn::Result<Option<LoadedFile>> {
+    let file = module
+        .attrs
+        .iter()
+        .find_map(|attr| match parse_attr(attr.clone()) {
+            Ok(ParseAttrStatus::Attr(parsed)) => parsed.into_iter().find_map(|i| match i {
+                (Attr::File(path), span) => Some((path, span)),
+                _ => None,
+            }),
+            _ => None,
+        });
+    let Some((path, span)) = file else {
+        return Ok(None);
+    };
+
+    let brace = match &module.content {
+        Some((brace, items)) if items.is_empty() => *brace,
+        _ => {
+            return Err(syn::Error::new(
+                span,
+                "a module with `file = \"...\"` should be empty (`mod name {}`), its contents are read from the file",
+            ))
+        }
+    };
+
+    let (full_path, text) = read_manifest_file(&LitStr::new(&path, span))?;
+    if let Some((line, column, message)) = parse_error_location(&text) {
+        return Err(syn::Error::new(
+            span,
+            format!("couldn't parse {full_path}:{line}:{column}: {message}"),
+        ));
+    }
+
+    // Stable rust has no way to make spans that point into another file, so the
+    // compiler reports problems in the file at the `file = "..."` attribute.
+    let tokens = proc_macro2::TokenStream::from_str(&text)
+        .map_err(|e| syn::Error::new(span, format!("couldn't parse {full_path}: {e}")))?;
+    let file: File = syn::parse2(respan(tokens, span))
+        .map_err(|e| syn::Error::new(span, format!("couldn't parse {full_path}: {e}")))?;
+
+    // inner attributes of the file (like `#![allow(...)]`) belong to the module
+    module.attrs.extend(file.attrs);
+    module.content = Some((brace, file.items));
+    Ok(Some(LoadedFile {
+        path: full_path,
+        text,
+        brace: brace.span,
+    }))
+}
+
+/// Where parsing `text` as a file fails, as a (1-based) line and column
+fn parse_error_location(text: &str) -> Option<(usize, usize, String)> {
+    let _fallback = Fallback::force();
+    let error = syn::parse_file(text).err()?;
+    let start = error.span().start();
+    Some((start.line, start.column + 1, error.to_string()))
+}
+
+fn respan(tokens: TokenStream2, span: Span2) -> TokenStream2 {
+    tokens
+        .into_iter()
+        .map(|mut tt| {
+            if let TokenTree::Group(g) = &tt {
+                let mut group = Group::new(g.delimiter(), respan(g.stream(), span));
+                group.set_span(span);
+                tt = TokenTree::Group(group);
+            } else {
+                tt.set_span(span);
+            }
+            tt
+        })
+        .collect()
+}
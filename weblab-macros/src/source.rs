@@ -0,0 +1,359 @@
+use crate::open::unindent;
+use proc_macro2::{Delimiter, LineColumn, Spacing, Span, TokenStream, TokenTree};
+use quote::quote;
+use std::ops::Range;
+use syn::visit::{self, Visit};
+use syn::{AttrStyle, Expr, File, ForeignItem, ImplItem, Item, ItemMod, Pat, TraitItem, Type};
+
+/// The source text of a module as the author wrote it, used to recover the
+/// comments and formatting that are lost when turning tokens into a string.
+struct Source {
+    text: String,
+    start: LineColumn,
+    /// byte offsets in `text` where every line starts
+    line_starts: Vec<usize>,
+}
+
+impl Source {
+    fn new(span: Span) -> Option<Self> {
+        let text = span.source_text()?;
+        let start = span.start();
+        // line 0 means the compiler doesn't tell us where tokens are
+        if start.line == 0 {
+            return None;
+        }
+
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        Some(Self {
+            text,
+            start,
+            line_starts,
+        })
+    }
+
+    /// The byte offset in `text` of a position in the file
+    fn offset(&self, pos: LineColumn) -> Option<usize> {
+        let line = pos.line.checked_sub(self.start.line)?;
+        let line_start = *self.line_starts.get(line)?;
+        let column = if line == 0 {
+            pos.column.checked_sub(self.start.column)?
+        } else {
+            pos.column
+        };
+
+        let rest = &self.text[line_start..];
+        let offset = match rest.char_indices().nth(column) {
+            Some((i, _)) => i,
+            None if rest.chars().count() == column => rest.len(),
+            None => return None,
+        };
+
+        Some(line_start + offset)
+    }
+
+    /// Where `tt` is in `text`, if it was written there
+    fn range(&self, tt: &TokenTree) -> Option<Range<usize>> {
+        let span = tt.span();
+        let range = self.offset(span.start())?..self.offset(span.end())?;
+        let text = self.text.get(range.clone())?;
+
+        let found = match tt {
+            TokenTree::Group(g) if g.delimiter() == Delimiter::None => return None,
+            TokenTree::Group(g) => {
+                let (open, close) = delimiters(g.delimiter());
+                (text.starts_with(open) && text.ends_with(close)) || is_doc_comment(text)
+            }
+            TokenTree::Punct(p) if p.as_char() == '#' || p.as_char() == '!' => {
+                text == p.to_string() || is_doc_comment(text)
+            }
+            tt => text == tt.to_string(),
+        };
+
+        found.then_some(range)
+    }
+
+    /// The whitespace before `start`, if there is nothing else on its line
+    fn indentation(&self, start: usize) -> Option<&str> {
+        let line_start = self.text[..start].rfind('\n')? + 1;
+        let indent = &self.text[line_start..start];
+        indent.trim().is_empty().then_some(indent)
+    }
+}
+
+fn delimiters(delimiter: Delimiter) -> (&'static str, &'static str) {
+    match delimiter {
+        Delimiter::Parenthesis => ("(", ")"),
+        Delimiter::Brace => ("{", "}"),
+        Delimiter::Bracket => ("[", "]"),
+        Delimiter::None => ("", ""),
+    }
+}
+
+fn is_doc_comment(text: &str) -> bool {
+    text.starts_with("//") || text.starts_with("/*")
+}
+
+/// Whether `text` only contains whitespace and (non-doc) comments
+fn is_trivia(text: &str) -> bool {
+    text.parse::<TokenStream>()
+        .map(|i| i.is_empty())
+        .unwrap_or(false)
+}
+
+/// Whether the group written as `text` still contains the same tokens
+fn unchanged(text: &str, tt: &TokenTree) -> bool {
+    text.parse::<TokenStream>()
+        .map(|i| i.to_string() == tt.to_string())
+        .unwrap_or(false)
+}
+
+struct Renderer<'a> {
+    source: &'a Source,
+    out: String,
+}
+
+impl Renderer<'_> {
+    /// Renders `tokens`, which replace what was written between `start` and
+    /// `end` (or somewhere unknown, for tokens the macro made up). Whitespace
+    /// and comments between tokens that are still next to each other are
+    /// copied from the source.
+    fn render(&mut self, tokens: TokenStream, start: Option<usize>, end: Option<usize>) {
+        let mut last_end = start;
+        let mut joint = false;
+        let mut tokens = tokens.into_iter().peekable();
+
+        while let Some(tt) = tokens.next() {
+            let range = self.source.range(&tt).filter(|r| {
+                start.is_none_or(|start| r.start >= start)
+                    && end.is_none_or(|end| r.end <= end)
+                    && last_end.is_none_or(|end| end <= r.start)
+            });
+
+            if !joint {
+                self.separator(last_end, range.as_ref().map(|r| r.start));
+            }
+            joint = matches!(&tt, TokenTree::Punct(p) if p.spacing() == Spacing::Joint);
+
+            let Some(range) = range else {
+                // a token the macro made up (like `todo!()` in a template)
+                match tt {
+                    TokenTree::Group(g) if g.delimiter() != Delimiter::None => {
+                        let (open, close) = delimiters(g.delimiter());
+                        self.out.push_str(open);
+                        self.render(g.stream(), None, None);
+                        self.out.push_str(close);
+                    }
+                    tt => self.out.push_str(&tt.to_string()),
+                }
+                last_end = None;
+                continue;
+            };
+
+            let text = &self.source.text[range.clone()];
+            if is_doc_comment(text) {
+                // `#`, `!` and `[doc = "..."]` all come from the same comment
+                while tokens
+                    .peek()
+                    .and_then(|i| self.source.range(i))
+                    .is_some_and(|r| r == range)
+                {
+                    tokens.next();
+                }
+                self.out.push_str(text);
+                joint = false;
+            } else if let TokenTree::Group(g) = &tt {
+                if unchanged(text, &tt) {
+                    self.out.push_str(text);
+                } else {
+                    let (open, close) = delimiters(g.delimiter());
+                    self.out.push_str(open);
+                    self.render(g.stream(), Some(range.start + 1), Some(range.end - 1));
+                    self.out.push_str(close);
+                }
+            } else {
+                self.out.push_str(text);
+            }
+
+            last_end = Some(range.end);
+        }
+
+        if !joint && end.is_some() {
+            self.separator(last_end, end);
+        }
+    }
+
+    /// What to put between two tokens: the original whitespace and comments
+    /// if nothing was removed between them, otherwise a newline or a space.
+    fn separator(&mut self, last_end: Option<usize>, next_start: Option<usize>) {
+        let source = self.source;
+        if let (Some(end), Some(start)) = (last_end, next_start) {
+            let gap = &self.source.text[end..start];
+            if is_trivia(gap) {
+                self.out.push_str(gap);
+            } else if let Some(first) = gap.find('\n') {
+                // keep a comment after the previous token on the same line, and
+                // the comments right above the next token, but not what's in between
+                if is_trivia(&gap[..first]) {
+                    self.out.push_str(&gap[..first]);
+                }
+                self.out.push('\n');
+
+                let above = gap
+                    .match_indices('\n')
+                    .map(|(i, _)| i + 1)
+                    .find(|i| is_trivia(&gap[*i..]))
+                    .unwrap_or(gap.len());
+                self.out.push_str(&gap[above..]);
+            } else {
+                self.out.push(' ');
+            }
+        } else if let Some(indent) = next_start.and_then(|i| source.indentation(i)) {
+            // after tokens the macro made up, a token that starts its own line
+            self.out.push('\n');
+            self.out.push_str(indent);
+        } else if !self
+            .out
+            .ends_with(|c: char| c.is_whitespace() || "([{".contains(c))
+        {
+            self.out.push(' ');
+        }
+    }
+}
+
+/// Turns the (possibly changed) items of `module` back into source code.
+/// Where the items are unchanged, comments and formatting are kept as they
+/// were written, so authors can leave comments for students in templates.
+/// Without the source text, the items are pretty-printed.
+pub fn render_items(items: &[Item], module: Option<&ItemMod>) -> String {
+    // inner attributes (like `//!` comments) are part of the module's attributes
+    let inner = module
+        .into_iter()
+        .flat_map(|m| &m.attrs)
+        .filter(|a| matches!(a.style, AttrStyle::Inner(_)) && !a.path.is_ident("weblab"));
+    let tokens = quote! {#(#inner)* #(#items)*};
+
+    let source = module
+        .and_then(|m| m.content.as_ref())
+        .and_then(|(brace, _)| Source::new(brace.span));
+
+    match source {
+        Some(source) if source.text.len() >= 2 => {
+            let mut renderer = Renderer {
+                source: &source,
+                out: String::new(),
+            };
+            let end = source.text.len() - 1;
+            renderer.render(tokens, Some(1), Some(end));
+            let lines = renderer.out.lines().map(String::from).collect::<Vec<_>>();
+            unindent(&lines).trim().to_string()
+        }
+        _ => pretty_print(&tokens).unwrap_or_else(|| tokens.to_string()),
+    }
+}
+
+/// Pretty-prints `tokens` as a file, which also turns `#[doc = "..."]`
+/// attributes back into `///` and `//!` comments. prettyplease panics on
+/// syntax syn doesn't know (like `let ... else`), so that isn't printed.
+fn pretty_print(tokens: &TokenStream) -> Option<String> {
+    let file = syn::parse2::<File>(tokens.clone()).ok()?;
+    let mut verbatim = FindVerbatim(false);
+    verbatim.visit_file(&file);
+    if verbatim.0 {
+        return None;
+    }
+
+    Some(prettyplease::unparse(&file).trim().to_string())
+}
+
+/// Whether syn left any tokens it couldn't parse as they are
+struct FindVerbatim(bool);
+
+impl Visit<'_> for FindVerbatim {
+    fn visit_expr(&mut self, i: &Expr) {
+        self.0 |= matches!(i, Expr::Verbatim(_));
+        visit::visit_expr(self, i);
+    }
+
+    fn visit_foreign_item(&mut self, i: &ForeignItem) {
+        self.0 |= matches!(i, ForeignItem::Verbatim(_));
+        visit::visit_foreign_item(self, i);
+    }
+
+    fn visit_impl_item(&mut self, i: &ImplItem) {
+        self.0 |= matches!(i, ImplItem::Verbatim(_));
+        visit::visit_impl_item(self, i);
+    }
+
+    fn visit_item(&mut self, i: &Item) {
+        self.0 |= matches!(i, Item::Verbatim(_));
+        visit::visit_item(self, i);
+    }
+
+    fn visit_pat(&mut self, i: &Pat) {
+        self.0 |= matches!(i, Pat::Verbatim(_));
+        visit::visit_pat(self, i);
+    }
+
+    fn visit_trait_item(&mut self, i: &TraitItem) {
+        self.0 |= matches!(i, TraitItem::Verbatim(_));
+        visit::visit_trait_item(self, i);
+    }
+
+    fn visit_type(&mut self, i: &Type) {
+        self.0 |= matches!(i, Type::Verbatim(_));
+        visit::visit_type(self, i);
+    }
+}
+
+/// The text of the module with braces `outer`, with the text of each file in
+/// `files` put in the (empty) braces of the module it was read into.
+pub fn splice_files(outer: Span, files: &[(Span, &str)]) -> Option<String> {
+    let source = Source::new(outer)?;
+    let mut ranges = files
+        .iter()
+        .map(|(brace, text)| {
+            let range = source.offset(brace.start())?..source.offset(brace.end())?;
+            let braces = source.text.get(range.clone())?;
+            let empty = braces.starts_with('{') && braces.ends_with('}') && braces.len() >= 2;
+            (empty && is_trivia(&braces[1..braces.len() - 1])).then_some((range, *text))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    ranges.sort_by_key(|(range, _)| range.start);
+
+    let mut text = source.text.clone();
+    for (range, file) in ranges.into_iter().rev() {
+        text.replace_range(range, &format!("{{\n{file}\n}}"));
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doc_comments() {
+        let tokens = quote! {
+            #![doc = " A module"]
+            #[doc = " Adds"]
+            pub fn add() {}
+        };
+        assert_eq!(
+            pretty_print(&tokens).unwrap(),
+            "//! A module\n/// Adds\npub fn add() {}"
+        );
+    }
+
+    #[test]
+    fn unknown_syntax() {
+        let tokens = quote! {
+            fn f(x: Option<u32>) {
+                let Some(x) = x else { return };
+            }
+        };
+        assert_eq!(pretty_print(&tokens), None);
+    }
+}
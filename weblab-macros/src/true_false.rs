@@ -0,0 +1,49 @@
+use crate::asset::AssetFile;
+use crate::open::DocString;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitBool, LitStr, Token};
+
+pub struct TrueFalseQuestion {
+    pub title: String,
+    pub question_text: DocString,
+    pub assets: Vec<AssetFile>,
+    pub answer: Option<bool>,
+    pub explanation: DocString,
+}
+
+impl Parse for TrueFalseQuestion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut res = Self {
+            title: String::new(),
+            question_text: Default::default(),
+            assets: Vec::new(),
+            answer: None,
+            explanation: Default::default(),
+        };
+
+        while !input.is_empty() {
+            let field: Ident = input.parse()?;
+            let _colon: Token!(:) = input.parse()?;
+
+            match field.to_string().as_str() {
+                "title" => res.title = input.parse::<LitStr>()?.value(),
+                "question" => res.question_text = input.parse()?,
+                "answer" => res.answer = Some(input.parse::<LitBool>()?.value),
+                "explanation" => res.explanation = input.parse()?,
+                "asset" => res.assets.push(input.parse()?),
+                n => {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        format!("unexpected field name {}", n),
+                    ))
+                }
+            }
+
+            if input.peek(Token!(,)) {
+                let _comma = input.parse::<Token!(,)>()?;
+            }
+        }
+
+        Ok(res)
+    }
+}
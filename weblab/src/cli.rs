@@ -1,6 +1,6 @@
+use crate::format::format_code;
 use clap::{Parser, Subcommand};
 use path_slash::PathBufExt;
-use regex::Captures;
 use sanitize_filename::sanitize;
 use serde::Serialize;
 use std::collections::HashSet;
@@ -9,11 +9,15 @@ use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::{fs, io, process};
+use std::time::{Duration, Instant};
+use std::{fs, io, process, thread};
 use walkdir::WalkDir;
+use weblab_assignment_structure::markdown::{image_paths, is_relative, normalize_path};
 use weblab_assignment_structure::{
-    InlineQuestionList, MCOption, MCQuestion, MCStyle, OpenQuestion, ProgrammingAssignment,
-    WeblabAssignment, WeblabFolder,
+    ApiItem, Asset, ClozeBlank, ClozeQuestion, CompileResult, CompilesQuestion, ForbiddenConstruct,
+    InlineQuestionList, MCOption, MCQuestion, MCStyle, MatchingPair, MatchingQuestion,
+    NumericQuestion, OpenQuestion, OrderingQuestion, PredictOutputQuestion, ProgrammingAssignment,
+    QuestionPool, SourceFile, TrueFalseQuestion, WeblabAssignment, WeblabFolder,
 };
 use zip::write::FileOptions;
 use zip::ZipWriter;
@@ -24,6 +28,23 @@ pub struct Alternative {
     pub text: &'static str,
     #[serde(rename = "correct")]
     pub correct: bool,
+    #[serde(rename = "feedback")]
+    #[serde(skip_serializing_if = "str::is_empty")]
+    pub feedback: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct Blank {
+    #[serde(rename = "answers")]
+    pub answers: &'static [&'static str],
+}
+
+#[derive(Serialize)]
+pub struct Pair {
+    #[serde(rename = "left")]
+    pub left: &'static str,
+    #[serde(rename = "right")]
+    pub right: &'static str,
 }
 
 #[derive(Serialize, Default)]
@@ -112,10 +133,40 @@ struct Question {
     #[serde(default)]
     num_answers_expected: Option<usize>,
 
+    #[serde(rename = "answer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    answer: Option<f64>,
+
+    #[serde(rename = "tolerance")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    tolerance: Option<f64>,
+
+    #[serde(rename = "correctAnswer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    correct_answer: Option<bool>,
+
     #[serde(rename = "alternatives")]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
     alternatives: Vec<Alternative>,
+
+    #[serde(rename = "blanks")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    blanks: Vec<Blank>,
+
+    #[serde(rename = "pairs")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pairs: Vec<Pair>,
+
+    #[serde(rename = "items")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    items: Vec<&'static str>,
 }
 
 #[derive(Serialize)]
@@ -139,6 +190,11 @@ struct Folder {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     description_file: Option<String>,
+
+    #[serde(rename = "randomSelection")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    random_selection: Option<usize>,
 }
 
 #[derive(Serialize)]
@@ -160,6 +216,7 @@ impl AssignmentData {
         relative_paths: &[&str],
         inline: bool,
         assignment_text: &str,
+        pick: Option<usize>,
     ) -> Self {
         Self {
             question: None,
@@ -174,6 +231,7 @@ impl AssignmentData {
                 display_inline: inline,
                 description_file: (!assignment_text.is_empty())
                     .then(|| "assignment_description.md".to_string()),
+                random_selection: pick,
             }),
         }
     }
@@ -238,6 +296,7 @@ impl AssignmentData {
                     .map(|i| Alternative {
                         text: i.text,
                         correct: i.is_correct,
+                        feedback: i.feedback,
                     })
                     .collect(),
 
@@ -247,6 +306,98 @@ impl AssignmentData {
         }
     }
 
+    pub fn new_numeric(title: &str, explanation: &str, answer: f64, tolerance: f64) -> Self {
+        Self {
+            question: Some(Question {
+                r#type: "NumberQuestion".to_string(),
+                question_text_file: "question.md".to_string(),
+                explanation: Some(explanation.to_string()),
+
+                title: title.to_string(),
+                answer: Some(answer),
+                tolerance: Some(tolerance),
+
+                ..Default::default()
+            }),
+            folder: None,
+        }
+    }
+
+    pub fn new_true_false(title: &str, explanation: &str, answer: bool) -> Self {
+        Self {
+            question: Some(Question {
+                r#type: "TrueFalseQuestion".to_string(),
+                question_text_file: "question.md".to_string(),
+                explanation: Some(explanation.to_string()),
+
+                title: title.to_string(),
+                correct_answer: Some(answer),
+
+                ..Default::default()
+            }),
+            folder: None,
+        }
+    }
+
+    pub fn new_cloze(title: &str, explanation: &str, blanks: &[ClozeBlank]) -> Self {
+        Self {
+            question: Some(Question {
+                r#type: "FillInQuestion".to_string(),
+                question_text_file: "question.md".to_string(),
+                explanation: Some(explanation.to_string()),
+
+                title: title.to_string(),
+
+                blanks: blanks
+                    .iter()
+                    .map(|i| Blank { answers: i.answers })
+                    .collect(),
+
+                ..Default::default()
+            }),
+            folder: None,
+        }
+    }
+
+    pub fn new_matching(title: &str, explanation: &str, pairs: &[MatchingPair]) -> Self {
+        Self {
+            question: Some(Question {
+                r#type: "MatchingQuestion".to_string(),
+                question_text_file: "question.md".to_string(),
+                explanation: Some(explanation.to_string()),
+
+                title: title.to_string(),
+
+                pairs: pairs
+                    .iter()
+                    .map(|i| Pair {
+                        left: i.left,
+                        right: i.right,
+                    })
+                    .collect(),
+
+                ..Default::default()
+            }),
+            folder: None,
+        }
+    }
+
+    pub fn new_ordering(title: &str, explanation: &str, steps: &[&'static str]) -> Self {
+        Self {
+            question: Some(Question {
+                r#type: "OrderingQuestion".to_string(),
+                question_text_file: "question.md".to_string(),
+                explanation: Some(explanation.to_string()),
+
+                title: title.to_string(),
+                items: steps.to_vec(),
+
+                ..Default::default()
+            }),
+            folder: None,
+        }
+    }
+
     pub fn new_open(title: &str) -> Self {
         Self {
             question: Some(Question {
@@ -336,24 +487,240 @@ fn generate_zip(
 }
 
 fn write_and_fmt<P: AsRef<Path>, S: ToString>(path: P, code: S) -> io::Result<()> {
-    let r = regex::Regex::new(r#"#\[doc *= *"(.*)"\]"#).expect("should compile");
-    let rq = regex::Regex::new(r#"\\(.)"#).expect("should compile");
-
-    fs::write(&path, code.to_string())?;
+    fs::write(&path, format_code(&code.to_string()))
+}
 
-    process::Command::new("rustfmt")
-        .arg(path.as_ref())
-        .spawn()?
-        .wait()?;
+/// Formats `code` into `main.rs` in a temporary directory, and calls `f` with
+/// that file and the formatted code. The directory is removed afterwards.
+fn with_scratch_file<T>(
+    title: &str,
+    code: &str,
+    f: impl FnOnce(&Path, &str) -> Result<T, Box<dyn Error>>,
+) -> Result<(String, T), Box<dyn Error>> {
+    let dir = std::env::temp_dir().join(format!(
+        "weblab-scratch-{}-{}",
+        process::id(),
+        sanitize(title)
+    ));
+    fs::create_dir_all(&dir)?;
+
+    let result: Result<_, Box<dyn Error>> = (|| {
+        let source = dir.join("main.rs");
+        write_and_fmt(&source, code)?;
+        let code = fs::read_to_string(&source)?;
+
+        let res = f(&source, &code)?;
+        Ok((code, res))
+    })();
+
+    // a failure to clean up shouldn't hide why the program itself failed
+    let cleanup = fs::remove_dir_all(&dir);
+    let result = result?;
+    cleanup?;
+    Ok(result)
+}
 
-    let code = fs::read_to_string(&path)?;
-    let replaced = r.replace_all(&code, |caps: &Captures| {
-        format!("///{}", rq.replace_all(&caps[1], "$1"))
+/// How long the program of a predict-the-output question may run
+const RUN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs `command`, killing it when it takes longer than `timeout`
+fn output_with_timeout(
+    command: &mut process::Command,
+    timeout: Duration,
+) -> Result<Option<process::Output>, Box<dyn Error>> {
+    let mut child = command
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::piped())
+        .spawn()?;
+
+    // read the pipes on other threads, so the program doesn't block on a full pipe
+    let mut stdout = child.stdout.take().expect("piped");
+    let mut stderr = child.stderr.take().expect("piped");
+    let stdout = thread::spawn(move || {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    });
+    let stderr = thread::spawn(move || {
+        let mut buf = Vec::new();
+        stderr.read_to_end(&mut buf).map(|_| buf)
     });
 
-    fs::write(&path, replaced.as_ref())?;
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() > timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    Ok(Some(process::Output {
+        status,
+        stdout: stdout.join().expect("reader thread panicked")?,
+        stderr: stderr.join().expect("reader thread panicked")?,
+    }))
+}
 
-    Ok(())
+/// A fenced markdown code block. The closing fence has to be on its own line,
+/// even when the text doesn't end with a newline.
+fn code_block(language: &str, text: &str) -> String {
+    let newline = if text.is_empty() || text.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    format!("```{language}\n{text}{newline}```\n")
+}
+
+/// Compiles and runs the program of a predict-the-output question, and returns
+/// the (formatted) program together with what it printed.
+fn run_program(title: &str, code: &str) -> Result<(String, String), Box<dyn Error>> {
+    with_scratch_file(title, code, |source, _| {
+        let executable = source.with_extension("");
+        let output = process::Command::new("rustc")
+            .arg("--edition=2021")
+            .arg("-o")
+            .arg(&executable)
+            .arg(source)
+            .output()?;
+        if !output.status.success() {
+            return Err(Box::new(StringError(format!(
+                "the program of question `{title}` doesn't compile:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ))));
+        }
+
+        let output =
+            match output_with_timeout(&mut process::Command::new(&executable), RUN_TIMEOUT)? {
+                Some(output) => output,
+                None => {
+                    return Err(Box::new(StringError(format!(
+                        "the program of question `{title}` didn't finish within {} seconds",
+                        RUN_TIMEOUT.as_secs()
+                    ))))
+                }
+            };
+        if !output.status.success() {
+            return Err(Box::new(StringError(format!(
+                "the program of question `{title}` failed ({}):\n{}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ))));
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    })
+}
+
+/// Checks whether the snippet of a does-this-compile question gives the
+/// result its author claims, and returns the (formatted) snippet.
+fn check_compile_result(
+    title: &str,
+    code: &str,
+    expected: CompileResult,
+) -> Result<String, Box<dyn Error>> {
+    let (code, ()) = with_scratch_file(title, code, |source, code| {
+        // some errors (like overflowing constants in generic functions) only happen
+        // when generating code, so the snippet is compiled completely. A snippet with
+        // `fn main` is a program, otherwise `main` would be unused and not compiled.
+        let has_main = syn::parse_file(code).is_ok_and(|file| {
+            file.items
+                .iter()
+                .any(|i| matches!(i, syn::Item::Fn(f) if f.sig.ident == "main"))
+        });
+        let output = process::Command::new("rustc")
+            .arg("--edition=2021")
+            .arg(if has_main {
+                "--crate-type=bin"
+            } else {
+                "--crate-type=lib"
+            })
+            .arg("--out-dir")
+            .arg(source.parent().expect("file in a directory"))
+            .arg("-A")
+            .arg("warnings")
+            .arg(source)
+            .output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        match expected {
+            CompileResult::Compiles if !output.status.success() => {
+                Err(Box::new(StringError(format!(
+                    "the snippet of question `{title}` should compile, but it doesn't:\n{stderr}"
+                ))))
+            }
+            CompileResult::Fails if output.status.success() => {
+                Err(Box::new(StringError(format!(
+                    "the snippet of question `{title}` should fail to compile, but it compiles"
+                ))))
+            }
+            CompileResult::Error(code) if output.status.success() => {
+                Err(Box::new(StringError(format!(
+                    "the snippet of question `{title}` should fail to compile with error {code}, but it compiles"
+                ))))
+            }
+            CompileResult::Error(code) if !stderr.contains(&format!("error[{code}]")) => {
+                Err(Box::new(StringError(format!(
+                    "the snippet of question `{title}` should fail to compile with error {code}, but it fails with:\n{stderr}"
+                ))))
+            }
+            _ => Ok(()),
+        }
+    })?;
+
+    Ok(code)
+}
+
+/// cargo-weblabtest reads these comments from the top of the spec test to know
+/// which checks to run on the student's solution besides the tests themselves.
+fn spec_test_header(
+    clippy: bool,
+    require_fmt: bool,
+    forbidden: &[ForbiddenConstruct],
+    api: &[ApiItem],
+) -> String {
+    let mut header = String::new();
+    if clippy {
+        header.push_str("// weblab-check: clippy\n");
+    }
+    if require_fmt {
+        header.push_str("// weblab-check: rustfmt\n");
+    }
+    for i in forbidden {
+        header.push_str(&match i {
+            ForbiddenConstruct::Keyword(k) => format!("// weblab-forbid: keyword {k}\n"),
+            ForbiddenConstruct::Path(p) => format!("// weblab-forbid: path {p}\n"),
+            ForbiddenConstruct::Macro(m) => format!("// weblab-forbid: macro {m}\n"),
+        });
+    }
+    for ApiItem { path, signature } in api {
+        header.push_str(&format!("// weblab-signature: {path} = {signature}\n"));
+    }
+
+    header
+}
+
+/// WebLab has a single library file, so multiple library modules are put in
+/// one file, each after a `// weblab-file: name.rs` line. The docker runner
+/// splits them into separate files again. A single module called `library`
+/// is written as it is.
+fn library_file(library: &[SourceFile]) -> String {
+    match library {
+        [SourceFile {
+            name: "library",
+            contents,
+        }] => contents.to_string(),
+        files => files
+            .iter()
+            .map(|SourceFile { name, contents }| format!("// weblab-file: {name}.rs\n{contents}\n"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
 }
 
 fn generate_folder_internal(
@@ -364,6 +731,7 @@ fn generate_folder_internal(
         assignments,
     }: &WeblabFolder,
     inline: bool,
+    pick: Option<usize>,
 ) -> Result<(), Box<dyn Error>> {
     let p = path.as_ref().to_path_buf().join(sanitize(title));
 
@@ -375,6 +743,7 @@ fn generate_folder_internal(
         &assignments.iter().map(|i| i.title()).collect::<Vec<_>>(),
         inline,
         assignment_text,
+        pick,
     ))?;
     f.write_all(s.as_bytes())?;
 
@@ -390,6 +759,75 @@ fn generate_folder_internal(
     Ok(())
 }
 
+/// Where an asset is copied to, relative to the question: in the `assets` folder,
+/// keeping its (normalized) path. `..` above the crate root becomes `__parent__`,
+/// so those assets can't end up in the same place as others.
+fn asset_destination(path: &str) -> String {
+    let path = normalize_path(path)
+        .split('/')
+        .map(|i| if i == ".." { "__parent__" } else { i })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    format!("assets/{path}")
+}
+
+/// Changes relative markdown images (`![alt](img/tree.png)`) in `text` to point to
+/// where the assets are copied. Their paths are relative to the crate root (images
+/// in markdown files are made relative to the crate root by the macros). Fails when
+/// an image like that isn't an asset.
+fn rewrite_image_links(
+    title: &str,
+    text: &str,
+    assets: &[Asset],
+) -> Result<String, Box<dyn Error>> {
+    let mut res = String::new();
+    let mut last_end = 0;
+
+    for range in image_paths(text) {
+        let path = &text[range.clone()];
+        if !is_relative(path) {
+            // urls and absolute paths stay the way they are
+            continue;
+        }
+
+        let normalized = normalize_path(path);
+        let Some(asset) = assets.iter().find(|i| normalize_path(i.path) == normalized) else {
+            return Err(Box::new(StringError(format!(
+                "`{title}` uses the image `{path}`, which isn't one of its assets (add it with `#[weblab(asset = \"{normalized}\")]`, or `asset: \"{normalized}\"` in questions)"
+            ))));
+        };
+
+        res.push_str(&text[last_end..range.start]);
+        res.push_str(&asset_destination(asset.path));
+        last_end = range.end;
+    }
+    res.push_str(&text[last_end..]);
+
+    Ok(res)
+}
+
+/// Writes `question.md`, and copies the assets of the assignment next to it
+fn write_question(
+    p: &Path,
+    title: &str,
+    text: &str,
+    assets: &[Asset],
+) -> Result<(), Box<dyn Error>> {
+    for asset in assets {
+        let destination = p.join(asset_destination(asset.path));
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(destination, asset.contents)?;
+    }
+
+    let mut f = File::create(p.join("question.md"))?;
+    f.write_all(rewrite_image_links(title, text, assets)?.as_bytes())?;
+
+    Ok(())
+}
+
 fn recursive_generate_folder(
     path: impl AsRef<Path>,
     assignment: &WeblabAssignment,
@@ -398,8 +836,13 @@ fn recursive_generate_folder(
         WeblabAssignment::Programming(ProgrammingAssignment {
             title,
             assignment_text,
+            assets,
             mut library_visible,
             spectest_stdout_visible: _,
+            clippy,
+            require_fmt,
+            forbidden,
+            api,
             test,
             solution,
             library,
@@ -407,24 +850,24 @@ fn recursive_generate_folder(
             solution_template,
             checklist: _,
         }) => {
-            let p = &(*path.as_ref()).to_path_buf().join(&sanitize(title));
-            std::fs::create_dir_all(&p)?;
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
 
-            if let Some(l) = library {
-                write_and_fmt(p.join("library.rs"), l)?;
-            } else {
-                // write but force invisible when there's no library
-                write_and_fmt(p.join("library.rs"), "")?;
+            // write but force invisible when there's no library
+            if library.is_empty() {
                 library_visible = false;
             }
+            write_and_fmt(p.join("library.rs"), library_file(library))?;
 
             write_and_fmt(p.join("solution.rs"), solution)?;
             write_and_fmt(p.join("solution_template.rs"), solution_template)?;
-            write_and_fmt(p.join("test.rs"), test)?;
+            write_and_fmt(
+                p.join("test.rs"),
+                spec_test_header(*clippy, *require_fmt, forbidden, api) + test,
+            )?;
             write_and_fmt(p.join("test_template.rs"), test_template)?;
 
-            let mut f = File::create(p.join("question.md"))?;
-            f.write_all(assignment_text.as_bytes())?;
+            write_question(p, title, assignment_text, assets)?;
 
             let mut f = File::create(p.join("assignment-data.json"))?;
             let s = serde_json::to_string_pretty(&AssignmentData::new_programming(
@@ -438,17 +881,17 @@ fn recursive_generate_folder(
         WeblabAssignment::Open(OpenQuestion {
             title,
             assignment_text,
+            assets,
             expected_answer,
             ..
         }) => {
-            let p = &(*path.as_ref()).to_path_buf().join(&sanitize(title));
-            std::fs::create_dir_all(&p)?;
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
 
             let mut f = File::create(p.join("answer.md"))?;
             f.write_all(expected_answer.as_bytes())?;
 
-            let mut f = File::create(p.join("question.md"))?;
-            f.write_all(assignment_text.as_bytes())?;
+            write_question(p, title, assignment_text, assets)?;
 
             let mut f = File::create(p.join("assignment-data.json"))?;
             let s = serde_json::to_string_pretty(&AssignmentData::new_open(title))?;
@@ -457,16 +900,16 @@ fn recursive_generate_folder(
         WeblabAssignment::MultipleChoice(MCQuestion {
             title,
             assignment_text,
+            assets,
             options,
             randomize,
             style,
             explanation,
         }) => {
-            let p = &(*path.as_ref()).to_path_buf().join(&sanitize(title));
-            std::fs::create_dir_all(&p)?;
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
 
-            let mut f = File::create(p.join("question.md"))?;
-            f.write_all(assignment_text.as_bytes())?;
+            write_question(p, title, assignment_text, assets)?;
 
             let mut f = File::create(p.join("assignment-data.json"))?;
             let s = serde_json::to_string_pretty(&AssignmentData::new_mc(
@@ -474,12 +917,198 @@ fn recursive_generate_folder(
                 explanation,
                 *randomize,
                 *style,
-                *options,
+                options,
+            ))?;
+            f.write_all(s.as_bytes())?;
+        }
+        WeblabAssignment::Numeric(NumericQuestion {
+            title,
+            assignment_text,
+            assets,
+            answer,
+            tolerance,
+            explanation,
+        }) => {
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
+
+            write_question(p, title, assignment_text, assets)?;
+
+            let mut f = File::create(p.join("assignment-data.json"))?;
+            let s = serde_json::to_string_pretty(&AssignmentData::new_numeric(
+                title,
+                explanation,
+                *answer,
+                *tolerance,
+            ))?;
+            f.write_all(s.as_bytes())?;
+        }
+        WeblabAssignment::TrueFalse(TrueFalseQuestion {
+            title,
+            assignment_text,
+            assets,
+            answer,
+            explanation,
+        }) => {
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
+
+            write_question(p, title, assignment_text, assets)?;
+
+            let mut f = File::create(p.join("assignment-data.json"))?;
+            let s = serde_json::to_string_pretty(&AssignmentData::new_true_false(
+                title,
+                explanation,
+                *answer,
+            ))?;
+            f.write_all(s.as_bytes())?;
+        }
+        WeblabAssignment::Cloze(ClozeQuestion {
+            title,
+            assignment_text,
+            assets,
+            blanks,
+            explanation,
+        }) => {
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
+
+            write_question(p, title, assignment_text, assets)?;
+
+            let mut f = File::create(p.join("assignment-data.json"))?;
+            let s = serde_json::to_string_pretty(&AssignmentData::new_cloze(
+                title,
+                explanation,
+                blanks,
+            ))?;
+            f.write_all(s.as_bytes())?;
+        }
+        WeblabAssignment::PredictOutput(PredictOutputQuestion {
+            title,
+            assignment_text,
+            assets,
+            code,
+        }) => {
+            let (code, stdout) = run_program(title, code)?;
+
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
+
+            let mut f = File::create(p.join("answer.md"))?;
+            f.write_all(code_block("text", &stdout).as_bytes())?;
+
+            write_question(
+                p,
+                title,
+                &format!("{assignment_text}\n\n{}", code_block("rust", &code)),
+                assets,
+            )?;
+
+            let mut f = File::create(p.join("assignment-data.json"))?;
+            let s = serde_json::to_string_pretty(&AssignmentData::new_open(title))?;
+            f.write_all(s.as_bytes())?;
+        }
+        WeblabAssignment::Compiles(CompilesQuestion {
+            title,
+            assignment_text,
+            assets,
+            code,
+            expected,
+            explanation,
+        }) => {
+            let code = check_compile_result(title, code, *expected)?;
+
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
+
+            write_question(
+                p,
+                title,
+                &format!("{assignment_text}\n\n{}", code_block("rust", &code)),
+                assets,
+            )?;
+
+            let compiles = matches!(expected, CompileResult::Compiles);
+            let mut f = File::create(p.join("assignment-data.json"))?;
+            let s = serde_json::to_string_pretty(&AssignmentData::new_mc(
+                title,
+                explanation,
+                false,
+                MCStyle::NumCorrect(1),
+                &[
+                    MCOption {
+                        text: "It compiles",
+                        is_correct: compiles,
+                        feedback: "",
+                    },
+                    MCOption {
+                        text: "It doesn't compile",
+                        is_correct: !compiles,
+                        feedback: "",
+                    },
+                ],
+            ))?;
+            f.write_all(s.as_bytes())?;
+        }
+        WeblabAssignment::Matching(MatchingQuestion {
+            title,
+            assignment_text,
+            assets,
+            pairs,
+            explanation,
+        }) => {
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
+
+            write_question(p, title, assignment_text, assets)?;
+
+            let mut f = File::create(p.join("assignment-data.json"))?;
+            let s = serde_json::to_string_pretty(&AssignmentData::new_matching(
+                title,
+                explanation,
+                pairs,
+            ))?;
+            f.write_all(s.as_bytes())?;
+        }
+        WeblabAssignment::Ordering(OrderingQuestion {
+            title,
+            assignment_text,
+            assets,
+            steps,
+            explanation,
+        }) => {
+            let p = &(*path.as_ref()).to_path_buf().join(sanitize(title));
+            std::fs::create_dir_all(p)?;
+
+            write_question(p, title, assignment_text, assets)?;
+
+            let mut f = File::create(p.join("assignment-data.json"))?;
+            let s = serde_json::to_string_pretty(&AssignmentData::new_ordering(
+                title,
+                explanation,
+                steps,
             ))?;
             f.write_all(s.as_bytes())?;
         }
         WeblabAssignment::Folder(wf) => {
-            generate_folder_internal(path, wf, false)?;
+            generate_folder_internal(path, wf, false, None)?;
+        }
+        WeblabAssignment::Pool(QuestionPool {
+            title,
+            assignment_text,
+            pick,
+            assignments,
+        }) => {
+            generate_folder_internal(
+                path,
+                &WeblabFolder {
+                    title,
+                    assignment_text,
+                    assignments,
+                },
+                false,
+                Some(*pick),
+            )?;
         }
         WeblabAssignment::InlineQuestionList(InlineQuestionList {
             title,
@@ -494,6 +1123,7 @@ fn recursive_generate_folder(
                     assignments,
                 },
                 true,
+                None,
             )?;
         }
     }
@@ -532,9 +1162,23 @@ fn check_folder(title: &str, assignments: &[WeblabAssignment]) -> Result<(), Box
 
 fn check_assignment_tree(assignment: &WeblabAssignment) -> Result<(), Box<dyn Error>> {
     match assignment {
-        WeblabAssignment::Programming(_) => {}
-        WeblabAssignment::Open(_) => {}
-        WeblabAssignment::MultipleChoice(_) => {}
+        WeblabAssignment::Programming(_)
+        | WeblabAssignment::Open(_)
+        | WeblabAssignment::MultipleChoice(_)
+        | WeblabAssignment::Numeric(_)
+        | WeblabAssignment::TrueFalse(_)
+        | WeblabAssignment::Cloze(_)
+        | WeblabAssignment::PredictOutput(_)
+        | WeblabAssignment::Compiles(_)
+        | WeblabAssignment::Matching(_)
+        | WeblabAssignment::Ordering(_) => {
+            // fails when the text uses images that aren't assets
+            rewrite_image_links(
+                assignment.title(),
+                assignment.assignment_text(),
+                assignment.assets(),
+            )?;
+        }
         WeblabAssignment::Folder(WeblabFolder {
             title,
             assignments,
@@ -549,6 +1193,26 @@ fn check_assignment_tree(assignment: &WeblabAssignment) -> Result<(), Box<dyn Er
         }) => {
             check_folder(title, assignments)?;
         }
+        WeblabAssignment::Pool(QuestionPool {
+            title,
+            assignment_text: _,
+            pick,
+            assignments,
+        }) => {
+            if *pick == 0 {
+                return Err(Box::new(StringError(format!(
+                    "question pool `{title}` should pick at least one question"
+                ))));
+            }
+            if *pick > assignments.len() {
+                return Err(Box::new(StringError(format!(
+                    "question pool `{title}` picks {pick} questions, but it only contains {}",
+                    assignments.len()
+                ))));
+            }
+
+            check_folder(title, assignments)?;
+        }
     }
 
     Ok(())
@@ -590,3 +1254,67 @@ pub fn main(assignment: WeblabAssignment, args: &[String]) {
         eprintln!("{}", e);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASSETS: &[Asset] = &[
+        Asset {
+            path: "questions/img/tree.png",
+            contents: &[],
+        },
+        Asset {
+            path: "../shared/tree.png",
+            contents: &[],
+        },
+    ];
+
+    #[test]
+    fn image_links() {
+        let rewrite = |text| rewrite_image_links("q", text, ASSETS).unwrap();
+
+        assert_eq!(
+            rewrite("![tree](questions/img/tree.png \"a tree\")"),
+            "![tree](assets/questions/img/tree.png \"a tree\")"
+        );
+        assert_eq!(
+            rewrite("![tree](./questions/../questions/img/tree.png)"),
+            "![tree](assets/questions/img/tree.png)"
+        );
+        assert_eq!(
+            rewrite("![tree](../shared/tree.png)"),
+            "![tree](assets/__parent__/shared/tree.png)"
+        );
+        assert_eq!(
+            rewrite("![logo](https://example.com/tree.png) ![top](#top)"),
+            "![logo](https://example.com/tree.png) ![top](#top)"
+        );
+        assert_eq!(
+            rewrite("`f(x)[0](1)` and `![tree](tree.png)`"),
+            "`f(x)[0](1)` and `![tree](tree.png)`"
+        );
+    }
+
+    #[test]
+    fn errors_when_generating_code() {
+        // only found when the constant is evaluated for `check::<0>`
+        let code = "struct Check<const N: usize>;
+            impl<const N: usize> Check<N> { const OK: () = assert!(N > 0); }
+            fn check<const N: usize>() { Check::<N>::OK }
+            fn main() { check::<0>() }";
+
+        assert!(check_compile_result("q", code, CompileResult::Error("E0080")).is_ok());
+        assert!(check_compile_result("q", code, CompileResult::Fails).is_ok());
+        assert!(check_compile_result("q", code, CompileResult::Compiles).is_err());
+    }
+
+    #[test]
+    fn missing_images() {
+        // only exact paths match, `img/tree.png` isn't `questions/img/tree.png`
+        let error = rewrite_image_links("q", "![tree](img/tree.png)", ASSETS).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("`q` uses the image `img/tree.png`, which isn't one of its assets"));
+    }
+}
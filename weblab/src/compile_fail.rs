@@ -0,0 +1,194 @@
+//! Mistakes in assignments that have to be compile errors. These are doctests,
+//! since an assignment that doesn't compile can't be a normal test. The errors
+//! they should give are marked with `//~ ERROR`, which the tests crate checks.
+//!
+//! A correct assignment, which the ones below are changed from:
+//!
+//! ```
+//! use weblab::weblab;
+//!
+//! #[weblab(programming_assignment)]
+//! #[weblab(title = "sum")]
+//! mod assignment {
+//!     #[weblab(solution)]
+//!     mod solution {
+//!         #[weblab(stub)]
+//!         pub fn sum(xs: &[u32]) -> u32 {
+//!             xs.iter().sum()
+//!         }
+//!     }
+//!
+//!     #[weblab(test)]
+//!     mod test {
+//!         use super::solution::sum;
+//!
+//!         #[test]
+//!         #[weblab(performance)]
+//!         fn small() {
+//!             assert_eq!(sum(&[1, 2]), 3);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! A typo in an attribute (`solutoin`):
+//!
+//! ```compile_fail
+//! use weblab::weblab;
+//!
+//! #[weblab(programming_assignment)]
+//! mod assignment { //~ ERROR assignment has no reference solution
+//!     #[weblab(solutoin)] //~ ERROR unknown weblab attribute `solutoin`, did you mean `solution`?
+//!     mod solution {
+//!         pub fn sum(xs: &[u32]) -> u32 {
+//!             xs.iter().sum()
+//!         }
+//!     }
+//!
+//!     #[weblab(test)]
+//!     mod test {}
+//! }
+//! ```
+//!
+//! A typo in an attribute of the assignment itself:
+//!
+//! ```compile_fail
+//! use weblab::weblab;
+//!
+//! #[weblab(programming_assignment)]
+//! #[weblab(titel = "sum")] //~ ERROR unknown weblab attribute `titel`, did you mean `title`?
+//! mod assignment {
+//!     #[weblab(solution)]
+//!     mod solution {}
+//!
+//!     #[weblab(test)]
+//!     mod test {}
+//! }
+//! ```
+//!
+//! `#[weblab(stub)]` on something that isn't a function:
+//!
+//! ```compile_fail
+//! use weblab::weblab;
+//!
+//! #[weblab(programming_assignment)]
+//! mod assignment {
+//!     #[weblab(solution)]
+//!     mod solution {
+//!         #[weblab(stub)] //~ ERROR #[weblab(stub)] can only be used on functions and methods
+//!         pub struct Sum(pub u32);
+//!     }
+//!
+//!     #[weblab(test)]
+//!     mod test {}
+//! }
+//! ```
+//!
+//! `#[weblab(solution)]` on something that isn't a module:
+//!
+//! ```compile_fail
+//! use weblab::weblab;
+//!
+//! #[weblab(programming_assignment)]
+//! mod assignment {
+//!     #[weblab(solution)]
+//!     mod solution {
+//!         #[weblab(solution)] //~ ERROR #[weblab(solution)] can only be used on a module inside the assignment
+//!         pub fn sum(xs: &[u32]) -> u32 {
+//!             xs.iter().sum()
+//!         }
+//!     }
+//!
+//!     #[weblab(test)]
+//!     mod test {}
+//! }
+//! ```
+//!
+//! Multiple mistakes at once, in and outside of the solution:
+//!
+//! ```compile_fail
+//! use weblab::weblab;
+//!
+//! #[weblab(programming_assignment)]
+//! mod assignment {
+//!     #[weblab(solution)]
+//!     mod solution {
+//!         #[weblab(stubb)] //~ ERROR unknown weblab attribute `stubb`, did you mean `stub`?
+//!         pub fn sum(xs: &[u32]) -> u32 {
+//!             xs.iter().sum()
+//!         }
+//!     }
+//!
+//!     #[weblab(test)]
+//!     mod test {
+//!         #[weblab(performance)] //~ ERROR #[weblab(performance)] can only be used on test functions
+//!         mod nested {}
+//!     }
+//! }
+//! ```
+//!
+//! A multiple choice question with more correct options than it expects answers:
+//!
+//! ```compile_fail
+//! use weblab::mc_question;
+//!
+//! mc_question! {
+//!     title: "sum",
+//!     question:
+//!     /// What is `1 + 1`?
+//!
+//!     option "2" correct,
+//!     option "0b10" correct, //~ ERROR 2 options are marked as correct, but the question expects 1 answer
+//!     option "11",
+//! }
+//! ```
+//!
+//! Or with fewer:
+//!
+//! ```compile_fail
+//! use weblab::mc_question;
+//!
+//! mc_question! {
+//!     title: "sum",
+//!     question:
+//!     /// What is `1 + 1`?
+//!
+//!     expect 2 answers, //~ ERROR this question expects 2 answers, but 1 option is marked as correct
+//!     option "2" correct,
+//!     option "11",
+//! }
+//! ```
+//!
+//! An option that occurs twice is only a warning, so it's an error when
+//! warnings are denied:
+//!
+//! ```compile_fail
+//! #![deny(warnings)]
+//! use weblab::mc_question;
+//!
+//! mc_question! {
+//!     title: "sum",
+//!     question:
+//!     /// What is `1 + 1`?
+//!
+//!     option "2" correct,
+//!     option "11",
+//!     option "2", //~ ERROR the option `2` occurs multiple times
+//! }
+//! ```
+//!
+//! But without the duplicate option it isn't:
+//!
+//! ```
+//! #![deny(warnings)]
+//! use weblab::mc_question;
+//!
+//! mc_question! {
+//!     title: "sum",
+//!     question:
+//!     /// What is `1 + 1`?
+//!
+//!     option "2" correct,
+//!     option "11",
+//! }
+//! ```
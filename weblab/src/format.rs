@@ -0,0 +1,65 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Formats generated code with rustfmt, when it's installed. The macros already
+/// keep the code as it was written where they can, and pretty-print it where
+/// they can't, so code that rustfmt can't format is returned as it is.
+pub fn format_code(code: &str) -> String {
+    rustfmt(code).unwrap_or_else(|| code.to_string())
+}
+
+/// Runs `rustfmt` on `code`, if it's installed and the code is valid
+fn rustfmt(code: &str) -> Option<String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    child.stdin.take()?.write_all(code.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macro_arguments() {
+        let code = "use super::solution::maximum;\n\n\
+            #[test]\n\
+            fn negative() {\n    \
+                assert_eq!(maximum(&[-1]), Some(-1));\n    \
+                assert_eq!(maximum(&[-3, -1, -2]), Some(-1));\n\
+            }\n";
+        assert_eq!(format_code(code), code);
+    }
+
+    #[test]
+    fn strings() {
+        let code = r#"fn f() -> &'static str { "not // a comment, \" /* or this */" }"#;
+        assert!(format_code(code).contains(r#""not // a comment, \" /* or this */""#));
+    }
+
+    #[test]
+    fn comment_lines() {
+        let code = "// weblab-check: clippy\n// weblab-signature: f = fn f()\n\n\
+            use super::solution::f;\n\n\
+            // a comment\nfn g() {}\n";
+        assert_eq!(format_code(code), code);
+    }
+
+    #[test]
+    fn invalid() {
+        let code = "fn f( {";
+        assert_eq!(format_code(code), code);
+    }
+}
@@ -1,8 +1,11 @@
-///! for library docs, refer to [https://github.com/jonay2000/weblab-rs](https://github.com/jonay2000/weblab-rs)
+//! for library docs, refer to [https://github.com/jonay2000/weblab-rs](https://github.com/jonay2000/weblab-rs)
 pub use weblab_assignment_structure::*;
 pub use weblab_macros::*;
 
 pub mod cli;
+#[cfg(doctest)]
+mod compile_fail;
+mod format;
 
 #[macro_export]
 macro_rules! weblab_assignment_info {
@@ -53,6 +56,14 @@ macro_rules! template_only {
 #[macro_export]
 macro_rules! weblab_folder {
     ($title: literal, $($module: path),* $(,)?) => {
+        $crate::weblab_folder!(@title $title, $($module),*);
+    };
+
+    ($name: ident, $($module: path),* $(,)?) => {
+        $crate::weblab_folder!(@title stringify!($name), $($module),*);
+    };
+
+    (@title $title: expr, $($module: path),*) => {
         pub mod __WEBLAB_ASSIGNMENT_METADATA {
             use $crate::*;
 
@@ -72,8 +83,37 @@ macro_rules! weblab_folder {
             });
         }
     };
+}
 
-    ($name: ident, $($module: path),* $(,)?) => {
-        weblab_folder!(stringify!($name), $($module),*);
+#[macro_export]
+macro_rules! question_pool {
+    ($title: literal, pick $pick: literal of $($module: path),* $(,)?) => {
+        $crate::question_pool!(@title $title, pick $pick of $($module),*);
+    };
+
+    ($name: ident, pick $pick: literal of $($module: path),* $(,)?) => {
+        $crate::question_pool!(@title stringify!($name), pick $pick of $($module),*);
+    };
+
+    (@title $title: expr, pick $pick: literal of $($module: path),*) => {
+        pub mod __WEBLAB_ASSIGNMENT_METADATA {
+            use $crate::*;
+
+            pub const ASSIGNMENT_INFO: WeblabAssignment = WeblabAssignment::Pool(QuestionPool {
+                title: $title,
+                assignment_text: "",
+                pick: $pick,
+                assignments: &[
+                    $(
+                        {
+                            use super::*;
+                            use $module as weblab_module;
+
+                            weblab_module::__WEBLAB_ASSIGNMENT_METADATA::ASSIGNMENT_INFO
+                        }
+                    ),*
+                ],
+            });
+        }
     };
 }